@@ -0,0 +1,383 @@
+//! `#[derive(Accounts)]`, generating the `TryFrom<&[AccountInfo]>` of an instruction's accounts
+//! context from per-field attributes.
+//!
+//! ```
+//! # use accounts_derive::Accounts;
+//! # use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+//! # pub const ID: Pubkey = [0; 32];
+//! #[derive(Accounts)]
+//! pub struct ClaimAccounts<'a> {
+//!     #[signer]
+//!     pub beneficiary: &'a AccountInfo,
+//!     #[pda(seeds = [b"vesting", beneficiary.key()], bump = escrow_bump)]
+//!     pub escrow: &'a AccountInfo,
+//!     #[mint]
+//!     pub mint: &'a AccountInfo,
+//!     #[ata(mint = mint, authority = escrow)]
+//!     pub vault: &'a AccountInfo,
+//!     pub token_program: &'a AccountInfo,
+//!     pub escrow_bump: [u8; 1],
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! Fields are destructured from the accounts slice in declaration order, then checked in that
+//! order, so the expansion is the same slice pattern and calls that would be written by hand:
+//!
+//! - `#[signer]`: `SignerAccount::check`.
+//! - `#[mint]`: `MintAccount::check`.
+//! - `#[check(Type)]`: `Type::check`, for any `AccountCheck`.
+//! - `#[ata(mint = .., authority = .., token_program = ..)]`: `AssociatedTokenAccount::check`
+//!   against other fields, `token_program` defaults to the field of that name.
+//! - `#[pda(seeds = [..], bump = .., program = ..)]`: the field must be the canonical address
+//!   of `seeds`, which may use the other fields, or `ProgramError::InvalidSeeds` is returned.
+//!   `bump` names a `[u8; 1]` field, not taken from the accounts, that receives the bump, and
+//!   `program` defaults to `crate::ID`.
+//! - `#[remaining]`: on the last field, takes the trailing accounts. A `&'a [AccountInfo]` field
+//!   gets them as they are, any other type is parsed from them with its
+//!   `TryFrom<&'a [AccountInfo]>` once the other fields are checked, which is how optional
+//!   trailing accounts are passed. Without it any trailing account is an error.
+//!
+//! The traits are looked up in the `pinocchio-helpers` crate, `#[accounts(helpers = path)]` on the
+//! struct points elsewhere.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Expr, Fields, Ident, Lifetime, Path, Result, Token, Type,
+};
+
+#[proc_macro_derive(
+    Accounts,
+    attributes(accounts, signer, mint, check, ata, pda, remaining)
+)]
+pub fn derive_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/*
+ * ==========================
+ * Attributes
+ * ==========================
+ */
+enum Constraint {
+    Signer,
+    Mint,
+    Check(Path),
+    Ata {
+        mint: Ident,
+        authority: Ident,
+        token_program: Option<Ident>,
+    },
+    Pda {
+        seeds: Vec<Expr>,
+        bump: Option<Ident>,
+        program: Option<Expr>,
+    },
+}
+
+/// `key = value` pairs of a list attribute.
+struct Args(Punctuated<Arg, Token![,]>);
+
+struct Arg {
+    key: Ident,
+    value: Expr,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(Self(Punctuated::parse_terminated(input)?))
+    }
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+
+        Ok(Self { key, value })
+    }
+}
+
+fn ident(expr: Expr) -> Result<Ident> {
+    match expr {
+        Expr::Path(path) if path.qself.is_none() => path.path.require_ident().cloned(),
+        expr => Err(Error::new(expr.span(), "expected a field name")),
+    }
+}
+
+fn parse_ata(args: Args, span: proc_macro2::Span) -> Result<Constraint> {
+    let (mut mint, mut authority, mut token_program) = (None, None, None);
+
+    for Arg { key, value } in args.0 {
+        match key.to_string().as_str() {
+            "mint" => mint = Some(ident(value)?),
+            "authority" => authority = Some(ident(value)?),
+            "token_program" => token_program = Some(ident(value)?),
+            _ => return Err(Error::new(key.span(), "unknown `ata` argument")),
+        }
+    }
+
+    Ok(Constraint::Ata {
+        mint: mint.ok_or_else(|| Error::new(span, "`ata` needs a `mint`"))?,
+        authority: authority.ok_or_else(|| Error::new(span, "`ata` needs an `authority`"))?,
+        token_program,
+    })
+}
+
+fn parse_pda(args: Args, span: proc_macro2::Span) -> Result<Constraint> {
+    let (mut seeds, mut bump, mut program) = (None, None, None);
+
+    for Arg { key, value } in args.0 {
+        match key.to_string().as_str() {
+            "seeds" => match value {
+                Expr::Array(array) => seeds = Some(array.elems.into_iter().collect()),
+                value => return Err(Error::new(value.span(), "expected `[seed, ..]`")),
+            },
+            "bump" => bump = Some(ident(value)?),
+            "program" => program = Some(value),
+            _ => return Err(Error::new(key.span(), "unknown `pda` argument")),
+        }
+    }
+
+    Ok(Constraint::Pda {
+        seeds: seeds.ok_or_else(|| Error::new(span, "`pda` needs `seeds`"))?,
+        bump,
+        program,
+    })
+}
+
+/// Whether `ty` is a slice reference, `&'a [AccountInfo]`.
+fn is_slice(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(r) if matches!(*r.elem, Type::Slice(_)))
+}
+
+/*
+ * ==========================
+ * Expansion
+ * ==========================
+ */
+struct Field {
+    ident: Ident,
+    ty: Type,
+    constraints: Vec<Constraint>,
+    remaining: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+
+    let lifetime: &Lifetime = match input.generics.lifetimes().collect::<Vec<_>>()[..] {
+        [param] if input.generics.params.len() == 1 => &param.lifetime,
+        _ => {
+            return Err(Error::new(
+                // The first extra parameter, the name if there is none
+                input
+                    .generics
+                    .params
+                    .iter()
+                    .nth(1)
+                    .map_or(name.span(), |p| p.span()),
+                "`Accounts` structs take exactly one lifetime, that of the accounts",
+            ));
+        }
+    };
+
+    let mut helpers: Path = syn::parse_quote!(::pinocchio_helpers);
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("accounts")) {
+        for Arg { key, value } in attr.parse_args::<Args>()?.0 {
+            match (key.to_string().as_str(), value) {
+                ("helpers", Expr::Path(path)) => helpers = path.path,
+                _ => return Err(Error::new(key.span(), "expected `helpers = path`")),
+            }
+        }
+    }
+
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(name.span(), "`Accounts` needs named fields")),
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "`Accounts` can only be derived on structs",
+            ))
+        }
+    };
+
+    let mut fields = Vec::with_capacity(named.len());
+    for field in named {
+        let ident = field.ident.clone().unwrap();
+        let mut constraints = Vec::new();
+        let mut remaining = false;
+
+        for attr in &field.attrs {
+            let path = attr.path();
+            let span = path.span();
+
+            if path.is_ident("signer") {
+                attr.meta.require_path_only()?;
+                constraints.push(Constraint::Signer);
+            } else if path.is_ident("mint") {
+                attr.meta.require_path_only()?;
+                constraints.push(Constraint::Mint);
+            } else if path.is_ident("check") {
+                constraints.push(Constraint::Check(attr.parse_args()?));
+            } else if path.is_ident("ata") {
+                constraints.push(parse_ata(attr.parse_args()?, span)?);
+            } else if path.is_ident("pda") {
+                constraints.push(parse_pda(attr.parse_args()?, span)?);
+            } else if path.is_ident("remaining") {
+                attr.meta.require_path_only()?;
+                remaining = true;
+            }
+        }
+
+        fields.push(Field {
+            ident,
+            ty: field.ty.clone(),
+            constraints,
+            remaining,
+        });
+    }
+
+    if let Some(position) = fields.iter().position(|f| f.remaining) {
+        if position != fields.len() - 1 {
+            return Err(Error::new(
+                fields[position].ident.span(),
+                "`#[remaining]` must be the last field",
+            ));
+        }
+    }
+
+    // Fields receiving a bump aren't accounts
+    let bumps: Vec<&Ident> = fields
+        .iter()
+        .flat_map(|f| &f.constraints)
+        .filter_map(|c| match c {
+            Constraint::Pda { bump, .. } => bump.as_ref(),
+            _ => None,
+        })
+        .collect();
+
+    for bump in &bumps {
+        if !fields.iter().any(|f| &&f.ident == bump) {
+            return Err(Error::new(bump.span(), "no such field"));
+        }
+    }
+
+    let pattern = fields
+        .iter()
+        .filter(|f| !bumps.contains(&&f.ident))
+        .map(|f| {
+            let ident = &f.ident;
+            match f.remaining {
+                true => quote!(#ident @ ..),
+                false => quote!(#ident),
+            }
+        });
+
+    let helpers = &helpers;
+    let checks = fields.iter().flat_map(|field| {
+        let account = &field.ident;
+
+        field.constraints.iter().map(move |constraint| match constraint {
+            Constraint::Signer => quote! {
+                <#helpers::SignerAccount as #helpers::AccountCheck>::check(#account)?;
+            },
+            Constraint::Mint => quote! {
+                <#helpers::MintAccount as #helpers::AccountCheck>::check(#account)?;
+            },
+            Constraint::Check(path) => quote! {
+                <#path as #helpers::AccountCheck>::check(#account)?;
+            },
+            Constraint::Ata {
+                mint,
+                authority,
+                token_program,
+            } => {
+                let token_program = token_program
+                    .clone()
+                    .unwrap_or_else(|| Ident::new("token_program", account.span()));
+
+                quote! {
+                    <#helpers::AssociatedTokenAccount as #helpers::AssociatedTokenAccountCheck>::check(
+                        #account, #mint, #authority, #token_program,
+                    )?;
+                }
+            }
+            Constraint::Pda {
+                seeds,
+                bump,
+                program,
+            } => {
+                let program = match program {
+                    Some(program) => quote!(#program),
+                    None => quote!(crate::ID),
+                };
+                let check = quote! {
+                    let (key, bump) = ::pinocchio::pubkey::find_program_address(
+                        &[#(#seeds),*],
+                        &#program,
+                    );
+                    if key.ne(#account.key()) {
+                        return Err(::pinocchio::program_error::ProgramError::InvalidSeeds);
+                    }
+                };
+
+                match bump {
+                    Some(bump) => quote!(let #bump = { #check [bump] };),
+                    None => quote!({ #check }),
+                }
+            }
+        })
+    });
+
+    // A trailing struct is parsed from the accounts left over
+    let trailing = fields
+        .iter()
+        .filter(|f| f.remaining && !is_slice(&f.ty))
+        .map(|f| {
+            let (ident, ty) = (&f.ident, &f.ty);
+            quote_spanned! {ty.span()=>
+                let #ident = <#ty as ::core::convert::TryFrom<
+                    &#lifetime [::pinocchio::account_info::AccountInfo],
+                >>::try_from(#ident)?;
+            }
+        });
+
+    let idents = fields.iter().map(|f| &f.ident);
+
+    Ok(quote! {
+        impl<#lifetime> ::core::convert::TryFrom<&#lifetime [::pinocchio::account_info::AccountInfo]>
+            for #name<#lifetime>
+        {
+            type Error = ::pinocchio::program_error::ProgramError;
+
+            fn try_from(
+                accounts: &#lifetime [::pinocchio::account_info::AccountInfo],
+            ) -> ::core::result::Result<Self, Self::Error> {
+                let [#(#pattern),*] = accounts else {
+                    return Err(::pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
+                };
+
+                #(#checks)*
+
+                #(#trailing)*
+
+                Ok(Self { #(#idents),* })
+            }
+        }
+    })
+}
@@ -0,0 +1,244 @@
+use accounts_derive::Accounts;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use pinocchio_helpers::AccountCheck;
+
+// `#[pda]` defaults to `crate::ID`
+pub const ID: Pubkey = [7; 32];
+
+/// The runtime's serialization of an account: borrow state, signer, writable and executable flags,
+/// resize delta, key, owner, lamports and data length, followed by the data.
+struct Buffer(Vec<u64>);
+
+impl Buffer {
+    const HEADER: usize = 88;
+
+    fn new(key: u8, owner: Pubkey, is_signer: bool, data_len: usize) -> Self {
+        let mut buffer = Self(vec![0; (Self::HEADER + data_len).div_ceil(8)]);
+        let bytes = buffer.bytes();
+
+        bytes[0] = u8::MAX; // not a duplicate, every borrow available
+        bytes[1] = is_signer as u8;
+        bytes[2] = 1;
+        bytes[8..40].copy_from_slice(&[key; 32]);
+        bytes[40..72].copy_from_slice(&owner);
+        bytes[80..88].copy_from_slice(&(data_len as u64).to_le_bytes());
+
+        buffer
+    }
+
+    fn bytes(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.0.as_mut_ptr() as *mut u8, self.0.len() * 8) }
+    }
+
+    fn info(&mut self) -> AccountInfo {
+        // `AccountInfo` is a single pointer to the serialized account
+        unsafe { core::mem::transmute::<*mut u64, AccountInfo>(self.0.as_mut_ptr()) }
+    }
+}
+
+fn signer(key: u8) -> Buffer {
+    Buffer::new(key, [0; 32], true, 0)
+}
+
+fn account(key: u8) -> Buffer {
+    Buffer::new(key, [0; 32], false, 0)
+}
+
+fn mint(key: u8) -> Buffer {
+    Buffer::new(key, pinocchio_token::ID, false, 82)
+}
+
+fn infos(buffers: &mut [Buffer]) -> Vec<AccountInfo> {
+    buffers.iter_mut().map(Buffer::info).collect()
+}
+
+/// Any writable account.
+struct WritableAccount;
+
+impl AccountCheck for WritableAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_writable() {
+            return Err(ProgramError::Immutable);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+struct MakeAccounts<'a> {
+    #[signer]
+    maker: &'a AccountInfo,
+    #[mint]
+    mint: &'a AccountInfo,
+    #[check(WritableAccount)]
+    escrow: &'a AccountInfo,
+}
+
+#[test]
+fn test_fields_in_order() {
+    let mut buffers = [signer(1), mint(2), account(3)];
+    let accounts = infos(&mut buffers);
+
+    let make = MakeAccounts::try_from(accounts.as_slice()).unwrap();
+
+    assert_eq!(make.maker.key(), &[1; 32]);
+    assert_eq!(make.mint.key(), &[2; 32]);
+    assert_eq!(make.escrow.key(), &[3; 32]);
+}
+
+#[test]
+fn test_exact_number_of_accounts() {
+    let mut buffers = [signer(1), mint(2), account(3), account(4)];
+    let accounts = infos(&mut buffers);
+
+    assert_eq!(
+        MakeAccounts::try_from(&accounts[..2]).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        MakeAccounts::try_from(accounts.as_slice()).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+#[test]
+fn test_checks() {
+    let mut buffers = [account(1), mint(2), account(3)];
+    assert_eq!(
+        MakeAccounts::try_from(infos(&mut buffers).as_slice()).err(),
+        Some(ProgramError::MissingRequiredSignature)
+    );
+
+    let mut buffers = [signer(1), account(2), account(3)];
+    assert_eq!(
+        MakeAccounts::try_from(infos(&mut buffers).as_slice()).err(),
+        Some(ProgramError::InvalidAccountOwner)
+    );
+
+    let mut buffers = [signer(1), mint(2), account(3)];
+    buffers[2].bytes()[2] = 0;
+    assert_eq!(
+        MakeAccounts::try_from(infos(&mut buffers).as_slice()).err(),
+        Some(ProgramError::Immutable)
+    );
+
+    // Fields are checked in declaration order
+    let mut buffers = [account(1), account(2), account(3)];
+    buffers[2].bytes()[2] = 0;
+    assert_eq!(
+        MakeAccounts::try_from(infos(&mut buffers).as_slice()).err(),
+        Some(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[allow(dead_code)] // only built on chain, see `test_bump_is_not_an_account`
+#[derive(Accounts)]
+struct VaultAccounts<'a> {
+    #[signer]
+    owner: &'a AccountInfo,
+    #[pda(seeds = [b"vault", owner.key()], bump = bump)]
+    vault: &'a AccountInfo,
+    bump: [u8; 1],
+}
+
+#[test]
+fn test_bump_is_not_an_account() {
+    let mut buffers = [signer(1), account(2), account(3)];
+    let accounts = infos(&mut buffers);
+
+    // The bump is derived, so `[owner, vault, bump]` is one account too many. Addresses can only
+    // be derived on chain, which is where two accounts get past the slice pattern.
+    assert_eq!(
+        VaultAccounts::try_from(accounts.as_slice()).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        VaultAccounts::try_from(&accounts[..1]).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+#[derive(Accounts)]
+struct BatchAccounts<'a> {
+    #[signer]
+    payer: &'a AccountInfo,
+    #[remaining]
+    offers: &'a [AccountInfo],
+}
+
+#[test]
+fn test_remaining_slice() {
+    let mut buffers = [signer(1), account(2), account(3)];
+    let accounts = infos(&mut buffers);
+
+    let batch = BatchAccounts::try_from(accounts.as_slice()).unwrap();
+    assert_eq!(batch.payer.key(), &[1; 32]);
+    assert_eq!(
+        batch.offers.iter().map(|a| a.key()[0]).collect::<Vec<_>>(),
+        [2, 3]
+    );
+
+    let batch = BatchAccounts::try_from(&accounts[..1]).unwrap();
+    assert!(batch.offers.is_empty());
+
+    assert_eq!(
+        BatchAccounts::try_from(&accounts[..0]).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
+
+/// Optional trailing `[mint]`.
+struct MintTrailingAccounts<'a> {
+    mint: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MintTrailingAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let mint = match accounts {
+            [] => None,
+            [mint] => {
+                pinocchio_helpers::MintAccount::check(mint)?;
+                Some(mint)
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+
+        Ok(Self { mint })
+    }
+}
+
+#[derive(Accounts)]
+struct RefundAccounts<'a> {
+    #[signer]
+    maker: &'a AccountInfo,
+    #[remaining]
+    trailing: MintTrailingAccounts<'a>,
+}
+
+#[test]
+fn test_remaining_struct() {
+    let mut buffers = [signer(1), mint(2), account(3)];
+    let accounts = infos(&mut buffers);
+
+    let refund = RefundAccounts::try_from(&accounts[..1]).unwrap();
+    assert_eq!(refund.maker.key(), &[1; 32]);
+    assert!(refund.trailing.mint.is_none());
+
+    let refund = RefundAccounts::try_from(&accounts[..2]).unwrap();
+    assert_eq!(refund.trailing.mint.unwrap().key(), &[2; 32]);
+
+    assert_eq!(
+        RefundAccounts::try_from(accounts.as_slice()).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+
+    // The trailing accounts are parsed once the other fields are checked
+    let mut buffers = [account(1), account(2)];
+    assert_eq!(
+        RefundAccounts::try_from(infos(&mut buffers).as_slice()).err(),
+        Some(ProgramError::MissingRequiredSignature)
+    );
+}
@@ -0,0 +1,12 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a> {
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint)]
+    pub vault: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+fn main() {}
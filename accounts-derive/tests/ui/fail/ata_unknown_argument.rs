@@ -0,0 +1,13 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = owner, payer = owner)]
+    pub vault: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+pub const ID: [u8; 32] = [7; 32];
+
+#[derive(Accounts)]
+pub struct VaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    #[pda(seeds = [b"vault", owner.key()], bump = vault_bump)]
+    pub vault: &'a AccountInfo,
+    pub bump: [u8; 1],
+}
+
+fn main() {}
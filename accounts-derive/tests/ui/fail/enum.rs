@@ -0,0 +1,9 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub enum ClaimAccounts<'a> {
+    Owner(&'a AccountInfo),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+#[accounts(helpers = "pinocchio_helpers")]
+pub struct ClaimAccounts<'a> {
+    #[signer]
+    pub owner: &'a AccountInfo,
+}
+
+fn main() {}
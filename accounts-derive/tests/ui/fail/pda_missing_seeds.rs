@@ -0,0 +1,12 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct VaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    #[pda(bump = bump)]
+    pub vault: &'a AccountInfo,
+    pub bump: [u8; 1],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct VaultAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    #[pda(seeds = owner.key())]
+    pub vault: &'a AccountInfo,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct TakeManyAccounts<'a> {
+    #[remaining]
+    pub offers: &'a [AccountInfo],
+    pub taker: &'a AccountInfo,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a> {
+    #[signer(owner)]
+    pub owner: &'a AccountInfo,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a>(&'a AccountInfo);
+
+fn main() {}
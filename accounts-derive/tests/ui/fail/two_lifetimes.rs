@@ -0,0 +1,10 @@
+use accounts_derive::Accounts;
+use pinocchio::account_info::AccountInfo;
+
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a, 'b> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'b AccountInfo,
+}
+
+fn main() {}
@@ -0,0 +1,55 @@
+use accounts_derive::Accounts;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+pub const ID: Pubkey = [7; 32];
+
+pub mod helpers {
+    pub use pinocchio_helpers::*;
+}
+
+pub struct Trailing<'a> {
+    pub market: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Trailing<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            market: accounts.first(),
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct MakeAccounts<'a> {
+    #[signer]
+    pub maker: &'a AccountInfo,
+    #[pda(seeds = [b"escrow", maker.key()], bump = escrow_bump)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = maker)]
+    pub maker_ata: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow, token_program = token_2022_program)]
+    #[check(helpers::TokenAccount)]
+    pub vault: &'a AccountInfo,
+    #[pda(seeds = [b"config"], program = ID)]
+    pub config: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub token_2022_program: &'a AccountInfo,
+    pub escrow_bump: [u8; 1],
+    #[remaining]
+    pub trailing: Trailing<'a>,
+}
+
+#[derive(Accounts)]
+#[accounts(helpers = helpers)]
+pub struct TakeManyAccounts<'a> {
+    #[signer]
+    pub taker: &'a AccountInfo,
+    #[remaining]
+    pub offers: &'a [AccountInfo],
+}
+
+fn main() {}
@@ -0,0 +1,67 @@
+use pinocchio::program_error::ProgramError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    /// The offer's expiry has passed, it can no longer be filled.
+    OfferExpired = 0,
+    /// The offer has no expiry, or its expiry has not passed yet.
+    OfferNotExpired = 1,
+    /// The expiry passed to `Make` is already in the past.
+    InvalidExpiry = 2,
+    /// The offer is private and the signer is not its designated taker.
+    InvalidTaker = 3,
+    /// The fee or referral share is above `Config::MAX_FEE_BPS`.
+    InvalidFeeBps = 4,
+    /// The signer is not the config admin (or the program upgrade authority on init).
+    Unauthorized = 5,
+    /// The program is paused by the config admin.
+    ProgramPaused = 6,
+    /// A basket has no legs or more than `BasketEscrow::MAX_LEGS` on a side, repeats a mint, or
+    /// the leg accounts don't match its legs.
+    InvalidLegs = 7,
+    /// An NFT-mode mint does not have a supply of 1 and 0 decimals.
+    NotAnNft = 8,
+    /// The NFT is not a verified member of the collection supplied by the maker.
+    InvalidCollection = 9,
+    /// The previous instruction is not an ed25519 verification of the order by its maker.
+    InvalidSignature = 10,
+    /// The signed order's nonce was already used.
+    OrderAlreadyFilled = 11,
+    /// The oracle price was published more than `PriceFeedAccount::MAX_AGE` seconds ago.
+    StalePrice = 12,
+    /// The oracle price is not positive, or its confidence interval is too wide.
+    InvalidPrice = 13,
+    /// `TakeMany` could not fill the taker's `total` within its limit price.
+    TotalNotReached = 14,
+    /// A milestone escrow has no milestones or more than `MilestoneEscrow::MAX_MILESTONES`.
+    InvalidMilestones = 15,
+    /// The escrow is under dispute, only its arbiter can settle it.
+    EscrowDisputed = 16,
+    /// The escrow has no arbiter, or is not under dispute.
+    NotDisputed = 17,
+    /// A vesting schedule does not satisfy `start <= cliff <= end` with `start < end`.
+    InvalidSchedule = 18,
+    /// Nothing has vested or streamed since the last claim or withdrawal.
+    NothingToClaim = 19,
+    /// The escrow has no hashlock, or the preimage does not hash to it.
+    InvalidPreimage = 20,
+    /// The escrow is hashlocked, it can only be redeemed with its preimage or refunded after
+    /// its timeout.
+    Hashlocked = 21,
+    /// A counter offer asks for less than the escrow's `min_fill`.
+    BelowMinFill = 22,
+    /// The escrow would cost the taker more `token_b` than their `max_pay`.
+    MaxPayExceeded = 23,
+    /// The vault holds less `token_a` than the taker's `min_receive`.
+    MinReceiveNotMet = 24,
+    /// The escrow account already has the current layout.
+    AlreadyMigrated = 25,
+    /// The referrer is the taker or the maker of the escrow.
+    InvalidReferrer = 26,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
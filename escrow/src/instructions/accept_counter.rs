@@ -0,0 +1,339 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, CounterOffer, Escrow, Market},
+};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, ConfigAccount, CounterOfferAccount, MarketAccount, MintAccount,
+    ProgramAccount, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct AcceptCounterAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub proposer: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+    pub counter: &'a AccountInfo,
+    pub a_mint: &'a AccountInfo,
+    pub b_mint: &'a AccountInfo,
+    pub escrow_ata: &'a AccountInfo, // vault
+    pub counter_ata_b: &'a AccountInfo,
+    pub proposer_ata_a: &'a AccountInfo,
+    pub maker_ata_a: &'a AccountInfo, // receives what the proposer didn't ask for
+    pub maker_ata_b: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub fee_recipient: &'a AccountInfo,
+    pub fee_recipient_ata_b: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub market: Option<&'a AccountInfo>, // required by indexed escrows
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptCounterAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, proposer, escrow, counter, a_mint, b_mint, escrow_ata, counter_ata_b, proposer_ata_a, maker_ata_a, maker_ata_b, config, fee_recipient, fee_recipient_ata_b, system_program, token_program, _associated_token_program, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+        ProgramAccount::check(escrow)?;
+        CounterOfferAccount::check(counter)?;
+        MintAccount::check(a_mint)?;
+        MintAccount::check(b_mint)?;
+        AssociatedTokenAccount::check(escrow_ata, a_mint, escrow, token_program)?;
+        AssociatedTokenAccount::check(counter_ata_b, b_mint, counter, token_program)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .fee_recipient
+            .ne(fee_recipient.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Optional trailing `[market]`, required by indexed escrows.
+        let market = match remaining {
+            [] => None,
+            [market] => {
+                MarketAccount::check(market)?;
+                Some(market)
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+
+        Ok(Self {
+            maker,
+            proposer,
+            escrow,
+            counter,
+            a_mint,
+            b_mint,
+            escrow_ata,
+            counter_ata_b,
+            proposer_ata_a,
+            maker_ata_a,
+            maker_ata_b,
+            config,
+            fee_recipient,
+            fee_recipient_ata_b,
+            system_program,
+            token_program,
+            market,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct AcceptCounter<'a> {
+    pub accounts: AcceptCounterAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptCounter<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = AcceptCounterAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            // The escrow address is derived from the maker, so this also checks that the signer
+            // is the escrow's maker.
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.token_a_mint.ne(accounts.a_mint.key())
+                || escrow.token_b_mint.ne(accounts.b_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.is_hashlocked() {
+                return Err(EscrowError::Hashlocked.into());
+            }
+
+            MarketAccount::check_escrow(accounts.market, escrow)?;
+        }
+
+        {
+            let data = accounts.counter.try_borrow_data()?;
+            let counter = CounterOffer::load(&data)?;
+
+            let counter_key = create_program_address(
+                &[
+                    b"counter",
+                    accounts.escrow.key(),
+                    accounts.proposer.key(),
+                    &counter.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if counter_key.ne(accounts.counter.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker,
+            accounts.proposer_ata_a,
+            accounts.proposer,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker,
+            accounts.maker_ata_a,
+            accounts.maker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker,
+            accounts.maker_ata_b,
+            accounts.maker,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker,
+            accounts.fee_recipient_ata_b,
+            accounts.fee_recipient,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptCounter<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    /// Settles the escrow on the counter offer's terms: the proposer gets the `token_a` they
+    /// asked for, the maker gets the deposit and whatever is left in the vault.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            if Config::load(&self.accounts.config.try_borrow_data()?)?.is_paused() {
+                return Err(EscrowError::ProgramPaused.into());
+            }
+
+            if escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferExpired.into());
+            }
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let (amount, deposit, counter_bump) = {
+            let data = self.accounts.counter.try_borrow_data()?;
+            let counter = CounterOffer::load(&data)?;
+
+            (counter.amount(), counter.deposit(), counter.bump)
+        };
+
+        let vault_amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                .amount();
+        let remainder = vault_amount
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+
+        let seed_binding = seed.to_le_bytes();
+        let escrow_seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let escrow_signers = [Signer::from(&escrow_seeds)];
+
+        // Vault -> Proposer
+        Transfer {
+            from: self.accounts.escrow_ata,
+            to: self.accounts.proposer_ata_a,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&escrow_signers)?;
+
+        // Vault -> Maker
+        if remainder.gt(&0) {
+            Transfer {
+                from: self.accounts.escrow_ata,
+                to: self.accounts.maker_ata_a,
+                authority: self.accounts.escrow,
+                amount: remainder,
+            }
+            .invoke_signed(&escrow_signers)?;
+        }
+
+        CloseAccount {
+            account: self.accounts.escrow_ata,
+            destination: self.accounts.maker,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&escrow_signers)?;
+
+        let counter_seeds = [
+            Seed::from(b"counter"),
+            Seed::from(self.accounts.escrow.key().as_ref()),
+            Seed::from(self.accounts.proposer.key().as_ref()),
+            Seed::from(&counter_bump),
+        ];
+        let counter_signers = [Signer::from(&counter_seeds)];
+
+        // The protocol fee is taken out of what the maker receives, as in `Take`.
+        let fee = Config::load(&self.accounts.config.try_borrow_data()?)?.fee(deposit)?;
+
+        // The account is swept before closing, anything sent to it on top of the deposit goes
+        // to the maker too.
+        let balance =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.counter_ata_b)?
+                .amount();
+
+        // Counter offer -> Maker
+        Transfer {
+            from: self.accounts.counter_ata_b,
+            to: self.accounts.maker_ata_b,
+            authority: self.accounts.counter,
+            amount: balance
+                .checked_sub(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        }
+        .invoke_signed(&counter_signers)?;
+
+        // Counter offer -> Fee recipient
+        if fee.gt(&0) {
+            Transfer {
+                from: self.accounts.counter_ata_b,
+                to: self.accounts.fee_recipient_ata_b,
+                authority: self.accounts.counter,
+                amount: fee,
+            }
+            .invoke_signed(&counter_signers)?;
+        }
+
+        // The proposer paid for the counter offer accounts, so they get the rent back.
+        CloseAccount {
+            account: self.accounts.counter_ata_b,
+            destination: self.accounts.proposer,
+            authority: self.accounts.counter,
+        }
+        .invoke_signed(&counter_signers)?;
+
+        ProgramAccount::close(self.accounts.counter, self.accounts.proposer)?;
+
+        if let Some(market) = self.accounts.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?
+                .record_fill(amount, deposit)?;
+        }
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
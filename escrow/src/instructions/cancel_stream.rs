@@ -0,0 +1,163 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{loadable::Loadable, state::StreamEscrow};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ProgramAccount, StreamAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct CancelStreamAccounts<'a> {
+    #[signer]
+    pub sender: &'a AccountInfo,
+    pub recipient: &'a AccountInfo,
+    #[check(StreamAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub sender_ata: &'a AccountInfo,
+    pub recipient_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Stops a stream, paying what accrued so far to the recipient and returning the rest to the
+/// sender.
+pub struct CancelStream<'a> {
+    pub accounts: CancelStreamAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelStream<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CancelStreamAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = StreamEscrow::load(&data)?;
+
+            // The escrow address is derived from the sender, so this also checks that the signer
+            // is the stream's sender.
+            let escrow_key = create_program_address(
+                &[
+                    b"stream",
+                    accounts.sender.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.recipient.ne(accounts.recipient.key())
+                || escrow.mint.ne(accounts.mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.sender,
+            accounts.sender_ata,
+            accounts.sender,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.sender,
+            accounts.recipient_ata,
+            accounts.recipient,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CancelStream<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    // Not gated by `Config::paused`, senders must always be able to exit.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, accrued) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = StreamEscrow::load(&data)?;
+
+            (
+                escrow.seed(),
+                escrow.bump,
+                escrow.withdrawable(Clock::get()?.unix_timestamp),
+            )
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"stream"),
+            Seed::from(self.accounts.sender.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let balance =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?.amount();
+
+        // Vault -> Recipient, what accrued and was not withdrawn yet
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.recipient_ata,
+            authority: self.accounts.escrow,
+            amount: accrued,
+        }
+        .invoke_signed(&signers)?;
+
+        // Vault -> Sender, what did not accrue
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.sender_ata,
+            authority: self.accounts.escrow,
+            amount: balance
+                .checked_sub(accrued)
+                .ok_or(ProgramError::InsufficientFunds)?,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.vault,
+            destination: self.accounts.sender,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.sender)?;
+
+        Ok(())
+    }
+}
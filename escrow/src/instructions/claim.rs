@@ -0,0 +1,155 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::VestingEscrow};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ProgramAccount, VestingAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct ClaimAccounts<'a> {
+    #[signer]
+    pub beneficiary: &'a AccountInfo,
+    pub funder: &'a AccountInfo, // gets the rent back after the last claim
+    #[check(VestingAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub beneficiary_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct Claim<'a> {
+    pub accounts: ClaimAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Claim<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ClaimAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = VestingEscrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"vesting",
+                    accounts.funder.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.mint.ne(accounts.mint.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.beneficiary.ne(accounts.beneficiary.key()) {
+                return Err(EscrowError::Unauthorized.into());
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.beneficiary,
+            accounts.beneficiary_ata,
+            accounts.beneficiary,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Claim<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    // Not gated by `Config::paused`, vested tokens belong to the beneficiary.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, amount, is_last) = {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+            let escrow = VestingEscrow::load_mut(data.as_mut())?;
+
+            let amount = escrow.claimable(Clock::get()?.unix_timestamp);
+
+            if amount.eq(&0) {
+                return Err(EscrowError::NothingToClaim.into());
+            }
+
+            escrow.set_claimed(escrow.claimed() + amount);
+
+            (
+                escrow.seed(),
+                escrow.bump,
+                amount,
+                escrow.is_fully_claimed(),
+            )
+        };
+
+        // The last claim empties the vault, so that anything sent to it on top of the vested
+        // total doesn't keep it from closing.
+        let amount = match is_last {
+            true => pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?
+                .amount(),
+            false => amount,
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"vesting"),
+            Seed::from(self.accounts.funder.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        // Vault -> Beneficiary
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.beneficiary_ata,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        if is_last {
+            CloseAccount {
+                account: self.accounts.vault,
+                destination: self.accounts.funder,
+                authority: self.accounts.escrow,
+            }
+            .invoke_signed(&signers)?;
+
+            ProgramAccount::close(self.accounts.escrow, self.accounts.funder)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Escrow, Market},
+};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, MarketAccount, MintAccount, ProgramAccount, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct CloseExpiredAccounts<'a> {
+    pub payer: &'a AccountInfo, // anyone, only pays for `maker_ata_a` if it does not exist
+    pub maker: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+    pub a_mint: &'a AccountInfo,
+    pub escrow_ata: &'a AccountInfo, // vault
+    pub maker_ata_a: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub market: Option<&'a AccountInfo>, // required by indexed escrows
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CloseExpiredAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, maker, escrow, a_mint, escrow_ata, maker_ata_a, system_program, token_program, _associated_token_program, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+        ProgramAccount::check(escrow)?;
+        MintAccount::check(a_mint)?;
+        AssociatedTokenAccount::check(escrow_ata, a_mint, escrow, token_program)?;
+
+        // Optional trailing `[market]`, required by indexed escrows.
+        let market = match remaining {
+            [] => None,
+            [market] => {
+                MarketAccount::check(market)?;
+                Some(market)
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+
+        Ok(Self {
+            payer,
+            maker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            maker_ata_a,
+            system_program,
+            token_program,
+            market,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct CloseExpired<'a> {
+    pub accounts: CloseExpiredAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CloseExpired<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CloseExpiredAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.token_a_mint.ne(accounts.a_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            MarketAccount::check_escrow(accounts.market, escrow)?;
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.payer,
+            accounts.maker_ata_a,
+            accounts.maker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CloseExpired<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &2;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            if !escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferNotExpired.into());
+            }
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                .amount();
+
+        // Vault -> Maker
+        Transfer {
+            from: self.accounts.escrow_ata,
+            to: self.accounts.maker_ata_a,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.escrow_ata,
+            destination: self.accounts.maker,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        if let Some(market) = self.accounts.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?.record_close()?;
+        }
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use accounts_derive::Accounts;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::MilestoneEscrow};
+
+use super::MilestoneAccount;
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct DisputeAccounts<'a> {
+    #[signer]
+    pub party: &'a AccountInfo, // the escrow's payer or payee
+    #[check(MilestoneAccount)]
+    pub escrow: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Freezes a milestone escrow until its arbiter settles it with `Resolve`.
+pub struct Dispute<'a> {
+    pub accounts: DisputeAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Dispute<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = DisputeAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Dispute<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    // Not gated by `Config::paused`, it only ever restricts who can move the funds.
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+        let escrow = MilestoneEscrow::load_mut(data.as_mut())?;
+
+        if escrow.payer.ne(self.accounts.party.key()) && escrow.payee.ne(self.accounts.party.key())
+        {
+            return Err(EscrowError::Unauthorized.into());
+        }
+
+        if !escrow.has_arbiter() {
+            return Err(EscrowError::NotDisputed.into());
+        }
+
+        if escrow.is_disputed() {
+            return Err(EscrowError::EscrowDisputed.into());
+        }
+
+        escrow.set_disputed();
+
+        Ok(())
+    }
+}
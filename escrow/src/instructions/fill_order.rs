@@ -0,0 +1,329 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, OrderNonce},
+};
+
+use super::{
+    AccountCheck, AssociateTokenAccountInit, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+    ConfigAccount, Ed25519Instruction, MintAccount, ProgramAccount, ProgramAccountInit,
+    SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct FillOrderAccounts<'a> {
+    pub taker: &'a AccountInfo,
+    pub maker: &'a AccountInfo, // signs the order off-chain, not the transaction
+    pub a_mint: &'a AccountInfo,
+    pub b_mint: &'a AccountInfo,
+    pub maker_ata_a: &'a AccountInfo, // approved `delegate` for at least `amount`
+    pub taker_ata_a: &'a AccountInfo,
+    pub taker_ata_b: &'a AccountInfo,
+    pub maker_ata_b: &'a AccountInfo,
+    pub delegate: &'a AccountInfo, // `[b"delegate"]` PDA, shared by every maker
+    pub nonce: &'a AccountInfo,    // `[b"nonce", maker, nonce]` PDA, created on fill
+    pub config: &'a AccountInfo,
+    pub fee_recipient: &'a AccountInfo,
+    pub fee_recipient_ata_b: &'a AccountInfo,
+    pub instructions: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FillOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [taker, maker, a_mint, b_mint, maker_ata_a, taker_ata_a, taker_ata_b, maker_ata_b, delegate, nonce, config, fee_recipient, fee_recipient_ata_b, instructions, system_program, token_program, _associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+        MintAccount::check(a_mint)?;
+        MintAccount::check(b_mint)?;
+        AssociatedTokenAccount::check(maker_ata_a, a_mint, maker, token_program)?;
+        AssociatedTokenAccount::check(taker_ata_b, b_mint, taker, token_program)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .fee_recipient
+            .ne(fee_recipient.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            taker,
+            maker,
+            a_mint,
+            b_mint,
+            maker_ata_a,
+            taker_ata_a,
+            taker_ata_b,
+            maker_ata_b,
+            delegate,
+            nonce,
+            config,
+            fee_recipient,
+            fee_recipient_ata_b,
+            instructions,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct FillOrderInstructionData {
+    pub amount: u64,
+    pub receive: u64,
+    pub seed: u64,
+    pub expiry: i64, // 0 => never expires
+    pub nonce: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FillOrderInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 5 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let seed = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let expiry = i64::from_le_bytes(data[24..32].try_into().unwrap());
+        let nonce = u64::from_le_bytes(data[32..40].try_into().unwrap());
+
+        if amount.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            amount,
+            receive,
+            seed,
+            expiry,
+            nonce,
+        })
+    }
+}
+
+impl FillOrderInstructionData {
+    pub const MESSAGE_LEN: usize = size_of::<Pubkey>() * 4 + size_of::<u64>() * 5;
+
+    /// The message signed by the maker:
+    /// `program_id || maker || token_a_mint || token_b_mint || amount || receive || seed ||
+    /// expiry || nonce`, integers little-endian.
+    pub fn message(
+        &self,
+        maker: &Pubkey,
+        token_a_mint: &Pubkey,
+        token_b_mint: &Pubkey,
+    ) -> [u8; Self::MESSAGE_LEN] {
+        let mut message = [0u8; Self::MESSAGE_LEN];
+
+        let fields: [&[u8]; 9] = [
+            &crate::ID,
+            maker,
+            token_a_mint,
+            token_b_mint,
+            &self.amount.to_le_bytes(),
+            &self.receive.to_le_bytes(),
+            &self.seed.to_le_bytes(),
+            &self.expiry.to_le_bytes(),
+            &self.nonce.to_le_bytes(),
+        ];
+
+        let mut offset = 0;
+        for field in fields {
+            message[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+
+        message
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct FillOrder<'a> {
+    pub accounts: FillOrderAccounts<'a>,
+    pub instruction_data: FillOrderInstructionData,
+    pub delegate_bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for FillOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = FillOrderInstructionData::try_from(data)?;
+        let accounts = FillOrderAccounts::try_from(accounts)?;
+
+        Ed25519Instruction::verify_previous(
+            accounts.instructions,
+            accounts.maker.key(),
+            &instruction_data.message(
+                accounts.maker.key(),
+                accounts.a_mint.key(),
+                accounts.b_mint.key(),
+            ),
+        )?;
+
+        let (delegate_key, delegate_bump) = find_program_address(&[b"delegate"], &crate::ID);
+        if delegate_key.ne(accounts.delegate.key()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Replay protection, an order can only ever be filled once.
+        let nonce_binding = instruction_data.nonce.to_le_bytes();
+        let (nonce_key, nonce_bump) = find_program_address(
+            &[b"nonce", accounts.maker.key(), &nonce_binding],
+            &crate::ID,
+        );
+        if nonce_key.ne(accounts.nonce.key()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if !accounts.nonce.data_is_empty() {
+            return Err(EscrowError::OrderAlreadyFilled.into());
+        }
+
+        let nonce_bump_binding = [nonce_bump];
+        let seeds = [
+            Seed::from(b"nonce"),
+            Seed::from(accounts.maker.key().as_ref()),
+            Seed::from(&nonce_binding),
+            Seed::from(&nonce_bump_binding),
+        ];
+
+        ProgramAccount::init(accounts.taker, accounts.nonce, &seeds, OrderNonce::SPACE)?;
+
+        {
+            let mut data = accounts.nonce.try_borrow_mut_data()?;
+            OrderNonce::init(data.as_mut())?.set_inner(
+                *accounts.maker.key(),
+                instruction_data.nonce,
+                nonce_bump_binding,
+            );
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.taker_ata_a,
+            accounts.taker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.maker_ata_b,
+            accounts.maker,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.fee_recipient_ata_b,
+            accounts.fee_recipient,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            delegate_bump: [delegate_bump],
+        })
+    }
+}
+
+impl<'a> FillOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let fee = {
+            let data = self.accounts.config.try_borrow_data()?;
+            let config = Config::load(&data)?;
+
+            if config.is_paused() {
+                return Err(EscrowError::ProgramPaused.into());
+            }
+
+            config.fee(self.instruction_data.receive)?
+        };
+
+        let expiry = self.instruction_data.expiry;
+        if expiry.ne(&0) && Clock::get()?.unix_timestamp > expiry {
+            return Err(EscrowError::OfferExpired.into());
+        }
+
+        let seeds = [Seed::from(b"delegate"), Seed::from(&self.delegate_bump)];
+        let signers = [Signer::from(&seeds)];
+
+        // Maker -> Taker, through the maker's delegation
+        Transfer {
+            from: self.accounts.maker_ata_a,
+            to: self.accounts.taker_ata_a,
+            authority: self.accounts.delegate,
+            amount: self.instruction_data.amount,
+        }
+        .invoke_signed(&signers)?;
+
+        // Taker -> Maker
+        Transfer {
+            from: self.accounts.taker_ata_b,
+            to: self.accounts.maker_ata_b,
+            authority: self.accounts.taker,
+            amount: self
+                .instruction_data
+                .receive
+                .checked_sub(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        }
+        .invoke()?;
+
+        // Taker -> Fee recipient
+        if fee.gt(&0) {
+            Transfer {
+                from: self.accounts.taker_ata_b,
+                to: self.accounts.fee_recipient_ata_b,
+                authority: self.accounts.taker,
+                amount: fee,
+            }
+            .invoke()?;
+        }
+
+        Ok(())
+    }
+}
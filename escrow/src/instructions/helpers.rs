@@ -1,329 +1,448 @@
 use pinocchio::{
     account_info::AccountInfo,
-    instruction::{Seed, Signer},
     program_error::ProgramError,
-    pubkey::find_program_address,
-    sysvars::{rent::Rent, Sysvar},
+    pubkey::{find_program_address, Pubkey},
+    sysvars::instructions::Instructions,
     ProgramResult,
 };
-use pinocchio_associated_token_account::instructions::Create;
-use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::instructions::{InitializeAccount3, InitializeMint2};
+use pinocchio_helpers::ProgramState;
 
-pub trait AccountCheck {
-    fn check(account: &AccountInfo) -> Result<(), ProgramError>;
-}
+use crate::{errors::EscrowError, loadable::Loadable};
+
+pub use pinocchio_helpers::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, MintAccount, MintInit, ProgramAccountInit, SignerAccount,
+    TokenAccount, TokenAccountInit,
+};
 
-pub struct SignerAccount;
+/// A single-supply, indivisible mint.
+pub struct NftMintAccount;
 
-impl AccountCheck for SignerAccount {
+impl AccountCheck for NftMintAccount {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        if !account.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        MintAccount::check(account)?;
+
+        let mint = pinocchio_token::state::Mint::from_account_info(account)?;
+        if mint.supply().ne(&1) || mint.decimals().ne(&0) {
+            return Err(EscrowError::NotAnNft.into());
         }
 
         Ok(())
     }
 }
 
-pub struct MintAccount;
+/// Checks that a program account holds the given type and hasn't been closed.
+#[inline(always)]
+fn check_discriminator(account: &AccountInfo, discriminator: u8) -> Result<(), ProgramError> {
+    crate::state::AccountHeader::load(&account.try_borrow_data()?)?.check(discriminator)
+}
+
+pub type ProgramAccount = pinocchio_helpers::ProgramAccount<crate::state::Escrow>;
+
+impl ProgramState for crate::state::Escrow {
+    const PROGRAM_ID: Pubkey = crate::ID;
+    const SPACE: usize = crate::state::Escrow::SPACE;
+
+    // Also accepts escrows created before the account header, see `Escrow::version`.
+    #[inline(always)]
+    fn check_data(data: &[u8]) -> Result<(), ProgramError> {
+        crate::state::Escrow::version(data).map(|_| ())
+    }
+}
 
-impl AccountCheck for MintAccount {
+pub struct ConfigAccount;
+
+impl AccountCheck for ConfigAccount {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        if !account.is_owned_by(&pinocchio_token::ID) {
+        if !account.is_owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
 
-        if account.data_len().ne(&pinocchio_token::state::Mint::LEN) {
+        if account.data_len().ne(&crate::state::Config::SPACE) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(())
+        check_discriminator(account, crate::state::Config::DISCRIMINATOR)
     }
 }
 
-pub trait MintInit {
-    fn init(
-        account: &AccountInfo,
-        payer: &AccountInfo,
-        decimals: u8,
-        mint_authority: &[u8; 32],
-        freeze_authority: Option<&[u8; 32]>,
-    ) -> Result<(), ProgramError>;
-    fn init_if_needed(
-        account: &AccountInfo,
-        payer: &AccountInfo,
-        decimals: u8,
-        mint_authority: &[u8; 32],
-        freeze_authority: Option<&[u8; 32]>,
-    ) -> Result<(), ProgramError>;
-}
+pub struct BasketAccount;
 
-impl MintInit for MintAccount {
-    fn init(
-        account: &AccountInfo,
-        payer: &AccountInfo,
-        decimals: u8,
-        mint_authority: &[u8; 32],
-        freeze_authority: Option<&[u8; 32]>,
-    ) -> Result<(), ProgramError> {
-        // Get required lamports for rent
-        let rent = Rent::get()?.minimum_balance(pinocchio_token::state::Mint::LEN);
-
-        // Fund the account with required Lamports
-        CreateAccount {
-            from: payer,
-            to: account,
-            lamports: rent,
-            space: pinocchio_token::state::Mint::LEN as u64,
-            owner: &pinocchio_token::ID,
-        }
-        .invoke()?;
-
-        InitializeMint2 {
-            mint: account,
-            decimals,
-            mint_authority,
-            freeze_authority,
-        }
-        .invoke()
-    }
+impl AccountCheck for BasketAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
 
-    fn init_if_needed(
-        account: &AccountInfo,
-        payer: &AccountInfo,
-        decimals: u8,
-        mint_authority: &[u8; 32],
-        freeze_authority: Option<&[u8; 32]>,
-    ) -> Result<(), ProgramError> {
-        match Self::check(account) {
-            Ok(_) => Ok(()),
-            Err(_) => Self::init(account, payer, decimals, mint_authority, freeze_authority),
+        // The full length depends on the leg counts, see `BasketEscrow::load`
+        if account.data_len() < crate::state::BasketEscrow::space(0, 0) {
+            return Err(ProgramError::InvalidAccountData);
         }
+
+        check_discriminator(account, crate::state::BasketEscrow::DISCRIMINATOR)
     }
 }
 
-pub struct TokenAccount;
+pub struct ReferralAccount;
 
-impl AccountCheck for TokenAccount {
+impl AccountCheck for ReferralAccount {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        if !account.is_owned_by(&pinocchio_token::ID) {
+        if !account.is_owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
 
-        if account
-            .data_len()
-            .ne(&pinocchio_token::state::TokenAccount::LEN)
-        {
+        if account.data_len().ne(&crate::state::Referral::SPACE) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(())
+        check_discriminator(account, crate::state::Referral::DISCRIMINATOR)
     }
 }
 
-pub trait TokenAccountInit {
-    fn init(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        payer: &AccountInfo,
-        owner: &[u8; 32],
-    ) -> Result<(), ProgramError>;
-    fn init_if_needed(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        payer: &AccountInfo,
-        owner: &[u8; 32],
-    ) -> Result<(), ProgramError>;
-}
+pub struct MarketAccount;
 
-impl TokenAccountInit for TokenAccount {
-    fn init(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        payer: &AccountInfo,
-        owner: &[u8; 32],
-    ) -> Result<(), ProgramError> {
-        let rent = Rent::get()?.minimum_balance(pinocchio_token::state::TokenAccount::LEN);
+impl AccountCheck for MarketAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
 
-        CreateAccount {
-            from: payer,
-            to: account,
-            lamports: rent,
-            space: pinocchio_token::state::TokenAccount::LEN as u64,
-            owner: &pinocchio_token::ID,
+        if account.data_len().ne(&crate::state::Market::SPACE) {
+            return Err(ProgramError::InvalidAccountData);
         }
-        .invoke()?;
 
-        InitializeAccount3 {
-            account,
-            mint,
-            owner,
+        check_discriminator(account, crate::state::Market::DISCRIMINATOR)
+    }
+}
+
+impl MarketAccount {
+    /// Splits an optional `Market` off the end of an instruction's trailing accounts.
+    #[inline(always)]
+    pub fn split_last(remaining: &[AccountInfo]) -> (&[AccountInfo], Option<&AccountInfo>) {
+        match remaining.split_last() {
+            Some((market, rest)) if Self::check(market).is_ok() => (rest, Some(market)),
+            _ => (remaining, None),
         }
-        .invoke()
     }
 
-    fn init_if_needed(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        payer: &AccountInfo,
-        owner: &[u8; 32],
+    /// Checks that `market` is passed exactly when the escrow is indexed, and that it is the
+    /// market of the escrow's pair.
+    pub fn check_escrow(
+        market: Option<&AccountInfo>,
+        escrow: &crate::state::Escrow,
     ) -> Result<(), ProgramError> {
-        match Self::check(account) {
-            Ok(_) => Ok(()),
-            Err(_) => Self::init(account, mint, payer, owner),
+        match (market, escrow.is_indexed()) {
+            (None, false) => Ok(()),
+            (None, true) => Err(ProgramError::NotEnoughAccountKeys),
+            (Some(_), false) => Err(ProgramError::InvalidArgument),
+            (Some(market), true) => {
+                let data = market.try_borrow_data()?;
+
+                if !crate::state::Market::load(&data)?
+                    .is_pair(&escrow.token_a_mint, &escrow.token_b_mint)
+                {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                Ok(())
+            }
         }
     }
 }
 
-pub struct AssociatedTokenAccount;
+/// Optional trailing `[market]` of the instructions closing a single escrow, required by indexed
+/// escrows.
+pub struct MarketTrailingAccounts<'a> {
+    pub market: Option<&'a AccountInfo>,
+}
 
-pub trait AssociatedTokenAccountCheck {
-    fn check(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        authority: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> Result<(), ProgramError>;
+impl<'a> TryFrom<&'a [AccountInfo]> for MarketTrailingAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let market = match accounts {
+            [] => None,
+            [market] => {
+                MarketAccount::check(market)?;
+                Some(market)
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+
+        Ok(Self { market })
+    }
 }
 
-impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
-    fn check(
-        account: &AccountInfo,
-        mint: &AccountInfo,
-        authority: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> Result<(), ProgramError> {
-        TokenAccount::check(account)?;
+pub struct MilestoneAccount;
 
-        let (ata, _) = find_program_address(
-            &[authority.key(), token_program.key(), mint.key()],
-            &pinocchio_associated_token_account::ID,
-        );
+impl AccountCheck for MilestoneAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
 
-        if ata.ne(account.key()) {
+        if account.data_len() < crate::state::MilestoneEscrow::space(0) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(())
+        check_discriminator(account, crate::state::MilestoneEscrow::DISCRIMINATOR)
     }
 }
 
-pub trait AssociateTokenAccountInit {
-    fn init(
-        payer: &AccountInfo,
-        account: &AccountInfo,
-        owner: &AccountInfo,
-        mint: &AccountInfo,
-        system_program: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> ProgramResult;
-    fn init_if_needed(
-        payer: &AccountInfo,
-        account: &AccountInfo,
-        owner: &AccountInfo,
-        mint: &AccountInfo,
-        system_program: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> ProgramResult;
-}
+pub struct VestingAccount;
 
-impl AssociateTokenAccountInit for AssociatedTokenAccount {
-    fn init(
-        payer: &AccountInfo,
-        account: &AccountInfo,
-        owner: &AccountInfo,
-        mint: &AccountInfo,
-        system_program: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> ProgramResult {
-        Create {
-            funding_account: payer,
-            account,
-            mint,
-            wallet: owner,
-            system_program,
-            token_program,
-        }
-        .invoke()
+impl AccountCheck for VestingAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        if account.data_len().ne(&crate::state::VestingEscrow::SPACE) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        check_discriminator(account, crate::state::VestingEscrow::DISCRIMINATOR)
     }
+}
 
-    fn init_if_needed(
-        payer: &AccountInfo,
-        account: &AccountInfo,
-        owner: &AccountInfo,
-        mint: &AccountInfo,
-        system_program: &AccountInfo,
-        token_program: &AccountInfo,
-    ) -> ProgramResult {
-        match Self::check(account, mint, payer, token_program) {
-            Ok(_) => Ok(()),
-            Err(_) => Self::init(payer, account, owner, mint, system_program, token_program),
+pub struct StreamAccount;
+
+impl AccountCheck for StreamAccount {
+    fn check(account: &AccountInfo) -> Result<(), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
         }
+
+        if account.data_len().ne(&crate::state::StreamEscrow::SPACE) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        check_discriminator(account, crate::state::StreamEscrow::DISCRIMINATOR)
     }
 }
 
-pub struct ProgramAccount;
+pub struct CounterOfferAccount;
 
-impl AccountCheck for ProgramAccount {
+impl AccountCheck for CounterOfferAccount {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
         if !account.is_owned_by(&crate::ID) {
             return Err(ProgramError::InvalidAccountOwner);
         }
 
-        if !account.data_len().ne(&crate::state::Escrow::LEN) {
+        if account.data_len().ne(&crate::state::CounterOffer::SPACE) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(())
+        check_discriminator(account, crate::state::CounterOffer::DISCRIMINATOR)
     }
 }
 
-pub trait ProgramAccountInit {
-    fn init<'a>(
-        payer: &AccountInfo,
+// metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = [
+    0x0b, 0x70, 0x65, 0xb1, 0xe3, 0xd1, 0x7c, 0x45, 0x38, 0x9d, 0x52, 0x7f, 0x6b, 0x04, 0xc3, 0xcd,
+    0x58, 0xb8, 0x6c, 0x73, 0x1a, 0xa0, 0xfd, 0xb5, 0x49, 0xb6, 0xd1, 0xbc, 0x03, 0xf8, 0x29, 0x46,
+];
+
+/// Metaplex token metadata of a mint.
+pub struct MetadataAccount;
+
+impl MetadataAccount {
+    /// Returns the collection `mint` is a verified member of, if any.
+    pub fn verified_collection(
         account: &AccountInfo,
-        seeds: &[Seed<'a>],
-        space: usize,
-    ) -> ProgramResult;
+        mint: &AccountInfo,
+    ) -> Result<Option<Pubkey>, ProgramError> {
+        if !account.is_owned_by(&TOKEN_METADATA_PROGRAM_ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let (metadata, _) = find_program_address(
+            &[b"metadata", &TOKEN_METADATA_PROGRAM_ID, mint.key()],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+        if metadata.ne(account.key()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let data = account.try_borrow_data()?;
+        Self::parse_collection(&data).ok_or(ProgramError::InvalidAccountData)
+    }
+
+    // Walks the borsh-encoded `Metadata` up to its `collection: Option<Collection>` field.
+    fn parse_collection(data: &[u8]) -> Option<Option<Pubkey>> {
+        let mut offset = 1 + 32 + 32; // key, update_authority, mint
+
+        // name, symbol, uri
+        for _ in 0..3 {
+            let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?);
+            offset += 4 + len as usize;
+        }
+
+        offset += 2; // seller_fee_basis_points
+
+        // creators: Option<Vec<Creator { address, verified, share }>>
+        if *data.get(offset)? == 1 {
+            let count = u32::from_le_bytes(data.get(offset + 1..offset + 5)?.try_into().ok()?);
+            offset += 4 + count as usize * 34;
+        }
+        offset += 1;
+
+        offset += 2; // primary_sale_happened, is_mutable
+
+        // edition_nonce, token_standard: Option<u8>
+        for _ in 0..2 {
+            offset += if *data.get(offset)? == 1 { 2 } else { 1 };
+        }
+
+        // collection: Option<Collection { verified, key }>
+        match data.get(offset)? {
+            0 => Some(None),
+            1 if *data.get(offset + 1)? == 1 => {
+                Some(Some(data.get(offset + 2..offset + 34)?.try_into().ok()?))
+            }
+            1 => Some(None),
+            _ => None,
+        }
+    }
 }
 
-impl ProgramAccountInit for ProgramAccount {
-    fn init<'a>(
-        payer: &AccountInfo,
-        account: &AccountInfo,
-        seeds: &[Seed<'a>],
-        space: usize,
+// Ed25519SigVerify111111111111111111111111111
+pub const ED25519_PROGRAM_ID: Pubkey = [
+    0x03, 0x7d, 0x46, 0xd6, 0x7c, 0x93, 0xfb, 0xbe, 0x12, 0xf9, 0x42, 0x8f, 0x83, 0x8d, 0x40, 0xff,
+    0x05, 0x70, 0x74, 0x49, 0x27, 0xf4, 0x8a, 0x64, 0xfc, 0xca, 0x70, 0x44, 0x80, 0x00, 0x00, 0x00,
+];
+
+/// An ed25519 precompile instruction, introspected through the `Instructions` sysvar.
+pub struct Ed25519Instruction;
+
+impl Ed25519Instruction {
+    /// Checks the instruction right before the current one verified `signer`'s signature of
+    /// `message`. The precompile fails the whole transaction on a bad signature, so only the
+    /// signed content has to be compared here.
+    pub fn verify_previous(
+        instructions: &AccountInfo,
+        signer: &Pubkey,
+        message: &[u8],
     ) -> ProgramResult {
-        let lamports = Rent::get()?.minimum_balance(space);
+        let instructions = Instructions::try_from(instructions)?;
+        let instruction = instructions
+            .get_instruction_relative(-1)
+            .map_err(|_| EscrowError::InvalidSignature)?;
+
+        if instruction.get_program_id().ne(&ED25519_PROGRAM_ID) {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        let data = instruction.get_instruction_data();
+
+        // [num_signatures: u8, padding: u8, Ed25519SignatureOffsets { signature_offset,
+        // signature_instruction_index, public_key_offset, public_key_instruction_index,
+        // message_data_offset, message_data_size, message_instruction_index }: [u16; 7]]
+        if data.len() < 16 || data[0].ne(&1) {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        let offset = |i: usize| u16::from_le_bytes([data[2 + i * 2], data[3 + i * 2]]);
 
-        let signer = [Signer::from(seeds)];
-        CreateAccount {
-            from: payer,
-            to: account,
-            lamports,
-            space: space as u64,
-            owner: &crate::ID,
+        // Everything must be read from the precompile instruction itself
+        if offset(1).ne(&u16::MAX) || offset(3).ne(&u16::MAX) || offset(6).ne(&u16::MAX) {
+            return Err(EscrowError::InvalidSignature.into());
         }
-        .invoke_signed(&signer)
+
+        let public_key_offset = offset(2) as usize;
+        let message_offset = offset(4) as usize;
+        let message_len = offset(5) as usize;
+
+        if data.get(public_key_offset..public_key_offset + 32) != Some(signer.as_ref())
+            || data.get(message_offset..message_offset + message_len) != Some(message)
+        {
+            return Err(EscrowError::InvalidSignature.into());
+        }
+
+        Ok(())
     }
 }
 
-pub trait AccountClose {
-    fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult;
-}
+// rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = [
+    0x0c, 0xb7, 0xfa, 0xbb, 0x52, 0xf7, 0xa6, 0x48, 0xbb, 0x5b, 0x31, 0x7d, 0x9a, 0x01, 0x8b, 0x90,
+    0x57, 0xcb, 0x02, 0x47, 0x74, 0xfa, 0xfe, 0x01, 0xe6, 0xc4, 0xdf, 0x98, 0xcc, 0x38, 0x58, 0x81,
+];
+
+/// Pyth `PriceUpdateV2` account, as posted by the Pyth receiver program.
+pub struct PriceFeedAccount;
+
+impl PriceFeedAccount {
+    const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+    // `VerificationLevel::Full`, partially verified updates are rejected.
+    const FULLY_VERIFIED: u8 = 1;
 
-impl AccountClose for ProgramAccount {
-    fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    /// Prices published longer ago than this, in seconds, are stale.
+    pub const MAX_AGE: i64 = 60;
+    /// Prices whose confidence interval is wider than this share of the price are rejected.
+    pub const MAX_CONFIDENCE_BPS: u64 = 100;
+
+    /// Returns the `(price, exponent)` of the feed, rejecting stale or low-confidence prices.
+    pub fn price(account: &AccountInfo, now: i64) -> Result<(u64, i32), ProgramError> {
+        if !account.is_owned_by(&PYTH_RECEIVER_PROGRAM_ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let data = account.try_borrow_data()?;
+
+        // discriminator, write_authority, verification_level, then the `PriceFeedMessage`
+        if data.len() < 8 + 32 + 1 + 84
+            || data[0..8].ne(&Self::DISCRIMINATOR)
+            || data[40].ne(&Self::FULLY_VERIFIED)
         {
-            let mut data = account.try_borrow_mut_data()?;
-            data[0] = 0xff;
+            return Err(ProgramError::InvalidAccountData);
         }
 
-        *destination.try_borrow_mut_lamports()? += *account.try_borrow_mut_lamports()?;
-        account.resize(1)?;
-        account.close()?;
+        // feed_id, price, conf, exponent, publish_time, ...
+        let message = &data[41..];
+        let price = i64::from_le_bytes(message[32..40].try_into().unwrap());
+        let conf = u64::from_le_bytes(message[40..48].try_into().unwrap());
+        let exponent = i32::from_le_bytes(message[48..52].try_into().unwrap());
+        let publish_time = i64::from_le_bytes(message[52..60].try_into().unwrap());
 
-        Ok(())
+        if now.saturating_sub(publish_time) > Self::MAX_AGE {
+            return Err(EscrowError::StalePrice.into());
+        }
+
+        let price = u64::try_from(price).map_err(|_| EscrowError::InvalidPrice)?;
+        if price.eq(&0)
+            || (conf as u128) * 10_000 > (price as u128) * Self::MAX_CONFIDENCE_BPS as u128
+        {
+            return Err(EscrowError::InvalidPrice.into());
+        }
+
+        Ok((price, exponent))
+    }
+}
+
+/// SHA-256 of `data` through the runtime's `sol_sha256` syscall.
+#[inline(always)]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    #[cfg(target_os = "solana")]
+    {
+        let mut hash = core::mem::MaybeUninit::<[u8; 32]>::uninit();
+        let vals = &[data];
+
+        unsafe {
+            pinocchio::syscalls::sol_sha256(
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                hash.as_mut_ptr() as *mut u8,
+            );
+
+            // SAFETY: The syscall has initialized the bytes.
+            hash.assume_init()
+        }
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        core::hint::black_box(data);
+        panic!("sha256 is only available on target `solana`")
     }
 }
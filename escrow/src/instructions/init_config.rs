@@ -0,0 +1,160 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+
+use crate::{errors::EscrowError, state::Config};
+
+use super::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount};
+
+// BPFLoaderUpgradeab1e11111111111111111111111
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey = [
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+];
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct InitConfigAccounts<'a> {
+    pub authority: &'a AccountInfo, // program upgrade authority, becomes the config admin
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, program_data, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        // Only the program's upgrade authority may create the config, otherwise anyone could
+        // front-run the deployment and set themselves as admin.
+        let (program_data_key, _) =
+            find_program_address(&[crate::ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+
+        if program_data_key.ne(program_data.key())
+            || !program_data.is_owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        {
+            // UpgradeableLoaderState::ProgramData { slot: u64, upgrade_authority_address: Option<Pubkey> }
+            let data = program_data.try_borrow_data()?;
+            if data.len() < 45
+                || data[0..4].ne(&3u32.to_le_bytes())
+                || data[12].ne(&1)
+                || data[13..45].ne(authority.key())
+            {
+                return Err(EscrowError::Unauthorized.into());
+            }
+        }
+
+        Ok(Self {
+            authority,
+            config,
+            system_program,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct InitConfigInstructionData {
+    pub fee_bps: u16,
+    pub referral_share_bps: u16, // share of the fee paid to the referrer, if any
+    pub fee_recipient: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() * 2 + size_of::<Pubkey>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_bps = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let referral_share_bps = u16::from_le_bytes(data[2..4].try_into().unwrap());
+        let fee_recipient: Pubkey = data[4..36].try_into().unwrap();
+
+        if fee_bps > Config::MAX_FEE_BPS || referral_share_bps > Config::MAX_FEE_BPS {
+            return Err(EscrowError::InvalidFeeBps.into());
+        }
+
+        Ok(Self {
+            fee_bps,
+            referral_share_bps,
+            fee_recipient,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct InitConfig<'a> {
+    pub accounts: InitConfigAccounts<'a>,
+    pub instruction_data: InitConfigInstructionData,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = InitConfigInstructionData::try_from(data)?;
+        let accounts = InitConfigAccounts::try_from(accounts)?;
+
+        let (config_key, bump) = find_program_address(&[b"config"], &crate::ID);
+        if config_key.ne(accounts.config.key()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump_binding = [bump];
+        let seeds = [Seed::from(b"config"), Seed::from(&bump_binding)];
+
+        ProgramAccount::init(accounts.authority, accounts.config, &seeds, Config::SPACE)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> InitConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+        let config = Config::init(data.as_mut())?;
+
+        config.set_inner(
+            *self.accounts.authority.key(),
+            self.instruction_data.fee_recipient,
+            self.instruction_data.fee_bps,
+            self.instruction_data.referral_share_bps,
+            self.bump,
+        );
+
+        Ok(())
+    }
+}
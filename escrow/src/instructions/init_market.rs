@@ -0,0 +1,77 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo, instruction::Seed, program_error::ProgramError, ProgramResult,
+};
+
+use crate::state::Market;
+
+use super::{ProgramAccount, ProgramAccountInit};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct InitMarketAccounts<'a> {
+    #[signer]
+    pub payer: &'a AccountInfo, // anyone
+    #[pda(seeds = [b"market", a_mint.key(), b_mint.key()], bump = market_bump)]
+    pub market: &'a AccountInfo,
+    #[mint]
+    pub a_mint: &'a AccountInfo,
+    #[mint]
+    pub b_mint: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub market_bump: [u8; 1],
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Creates the `Market` of a pair, escrows made afterwards may be indexed in it.
+pub struct InitMarket<'a> {
+    pub accounts: InitMarketAccounts<'a>,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitMarket<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = InitMarketAccounts::try_from(accounts)?;
+
+        let bump_binding = accounts.market_bump;
+        let seeds = [
+            Seed::from(b"market"),
+            Seed::from(accounts.a_mint.key()),
+            Seed::from(accounts.b_mint.key()),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init(accounts.payer, accounts.market, &seeds, Market::SPACE)?;
+
+        Ok(Self {
+            accounts,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> InitMarket<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.market.try_borrow_mut_data()?;
+
+        Market::init(data.as_mut())?.set_inner(
+            *self.accounts.a_mint.key(),
+            *self.accounts.b_mint.key(),
+            self.bump,
+        );
+
+        Ok(())
+    }
+}
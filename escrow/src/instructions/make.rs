@@ -1,58 +1,75 @@
+use accounts_derive::Accounts;
 use core::mem::size_of;
 use pinocchio::{
-    account_info::AccountInfo, instruction::Seed, program_error::ProgramError,
-    pubkey::find_program_address, ProgramResult,
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
 };
 use pinocchio_token::instructions::Transfer;
 
-use crate::state::Escrow;
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Escrow, Market},
+};
 
 use super::{
-    AccountCheck, AssociateTokenAccountInit, AssociatedTokenAccount, AssociatedTokenAccountCheck,
-    MintAccount, ProgramAccount, ProgramAccountInit, SignerAccount,
+    AccountCheck, AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount, MarketAccount,
+    MetadataAccount, NftMintAccount, ProgramAccount, ProgramAccountInit,
 };
 
+/// `MakeInstructionData::mode` flag, `token_a_mint` must be an NFT.
+pub const NFT_TOKEN_A: u8 = 0b01;
+/// `MakeInstructionData::mode` flag, `token_b_mint` must be an NFT.
+pub const NFT_TOKEN_B: u8 = 0b10;
+
 /*
  * =============================
  * Accounts Context
  * =============================
  */
+#[derive(Accounts)]
 pub struct MakeAccounts<'a> {
+    #[signer]
     pub maker: &'a AccountInfo,
     pub escrow: &'a AccountInfo,
+    #[mint]
     pub a_mint: &'a AccountInfo,
+    #[mint]
     pub b_mint: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = maker)]
     pub maker_ata: &'a AccountInfo,
     pub escrow_ata: &'a AccountInfo, // vault
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
     pub system_program: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    #[remaining]
+    pub trailing: MakeTrailingAccounts<'a>,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for MakeAccounts<'a> {
+/// Optional trailing `[metadata_a]` and `[market]`, in that order.
+pub struct MakeTrailingAccounts<'a> {
+    pub metadata_a: Option<&'a AccountInfo>, // only needed to verify a collection
+    pub market: Option<&'a AccountInfo>,     // indexes the escrow in its pair's `Market`
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MakeTrailingAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [maker, escrow, a_mint, b_mint, maker_ata, escrow_ata, system_program, token_program, _associated_token_program] =
-            accounts
-        else {
-            return Err(ProgramError::NotEnoughAccountKeys);
+        let (accounts, market) = MarketAccount::split_last(accounts);
+        let metadata_a = match accounts {
+            [] => None,
+            [metadata_a] => Some(metadata_a),
+            _ => return Err(ProgramError::InvalidArgument),
         };
 
-        SignerAccount::check(maker)?;
-        MintAccount::check(a_mint)?;
-        MintAccount::check(b_mint)?;
-        AssociatedTokenAccount::check(maker_ata, a_mint, maker, token_program)?;
-
-        Ok(Self {
-            maker,
-            escrow,
-            a_mint,
-            b_mint,
-            maker_ata,
-            escrow_ata,
-            system_program,
-            token_program,
-        })
+        Ok(Self { metadata_a, market })
     }
 }
 
@@ -65,28 +82,124 @@ pub struct MakeInstructionData {
     amount: u64,
     receive: u64,
     seed: u64,
+    expiry: i64,        // 0 => never expires
+    taker: Pubkey,      // zeroed => public offer
+    mode: u8,           // `NFT_TOKEN_A | NFT_TOKEN_B` flags
+    collection: Pubkey, // zeroed => not checked
+    price_feed: Pubkey, // zeroed => fixed `receive`, else `receive` is only a floor
+    spread_bps: u16,    // markup over the oracle price
+    hashlock: [u8; 32], // zeroed => no hashlock
+    min_fill: u64,      // 0 => any partial fill
 }
 
 impl<'a> TryFrom<&'a [u8]> for MakeInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() != size_of::<u64>() * 3 {
+        // The trailing `expiry`, `taker`, `mode`, `collection`, `(price_feed, spread_bps)`,
+        // `hashlock` and `min_fill` are optional, but each one can only be passed along with all
+        // the previous ones (which may be zeroed).
+        const LENS: [usize; 8] = [
+            size_of::<u64>() * 3,
+            size_of::<u64>() * 3 + size_of::<i64>(),
+            size_of::<u64>() * 3 + size_of::<i64>() + size_of::<Pubkey>(),
+            size_of::<u64>() * 3 + size_of::<i64>() + size_of::<Pubkey>() + size_of::<u8>(),
+            size_of::<u64>() * 3 + size_of::<i64>() + size_of::<Pubkey>() * 2 + size_of::<u8>(),
+            size_of::<u64>() * 3
+                + size_of::<i64>()
+                + size_of::<Pubkey>() * 3
+                + size_of::<u8>()
+                + size_of::<u16>(),
+            size_of::<u64>() * 3
+                + size_of::<i64>()
+                + size_of::<Pubkey>() * 3
+                + size_of::<u8>()
+                + size_of::<u16>()
+                + size_of::<[u8; 32]>(),
+            size_of::<u64>() * 3
+                + size_of::<i64>()
+                + size_of::<Pubkey>() * 3
+                + size_of::<u8>()
+                + size_of::<u16>()
+                + size_of::<[u8; 32]>()
+                + size_of::<u64>(),
+        ];
+
+        if !LENS.contains(&data.len()) {
             return Err(ProgramError::InvalidInstructionData);
         }
 
         let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let seed = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let expiry = data
+            .get(24..32)
+            .map_or(0, |expiry| i64::from_le_bytes(expiry.try_into().unwrap()));
+        let taker: Pubkey = data
+            .get(32..64)
+            .map_or(Pubkey::default(), |taker| taker.try_into().unwrap());
+        let mode = data.get(64).copied().unwrap_or(0);
+        let collection: Pubkey = data.get(65..97).map_or(Pubkey::default(), |collection| {
+            collection.try_into().unwrap()
+        });
+        let price_feed: Pubkey = data.get(97..129).map_or(Pubkey::default(), |price_feed| {
+            price_feed.try_into().unwrap()
+        });
+        let spread_bps = data.get(129..131).map_or(0, |spread_bps| {
+            u16::from_le_bytes(spread_bps.try_into().unwrap())
+        });
+        let hashlock: [u8; 32] = data
+            .get(131..163)
+            .map_or([0; 32], |hashlock| hashlock.try_into().unwrap());
+        let min_fill = data.get(163..171).map_or(0, |min_fill| {
+            u64::from_le_bytes(min_fill.try_into().unwrap())
+        });
 
         if amount.eq(&0) {
             return Err(ProgramError::InvalidInstructionData);
         }
 
+        // NFTs are traded whole, and a collection can only be checked on the offered NFT.
+        if mode & !(NFT_TOKEN_A | NFT_TOKEN_B) != 0
+            || (mode & NFT_TOKEN_A != 0 && amount.ne(&1))
+            || (mode & NFT_TOKEN_B != 0 && receive.ne(&1))
+            || (mode & NFT_TOKEN_A == 0 && collection.ne(&Pubkey::default()))
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // An oracle can't price a whole NFT, and a spread needs an oracle price to apply to.
+        if (price_feed.ne(&Pubkey::default()) && mode & NFT_TOKEN_B != 0)
+            || (price_feed.eq(&Pubkey::default()) && spread_bps.ne(&0))
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // A hashlock releases the vault to a designated taker before a timeout, and isn't paid
+        // for, so there is nothing for an oracle to price.
+        if hashlock.ne(&[0; 32])
+            && (taker.eq(&Pubkey::default()) || expiry.eq(&0) || price_feed.ne(&Pubkey::default()))
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // A minimum fill above the deposit could never be met.
+        if min_fill.gt(&amount) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         Ok(Self {
             amount,
             receive,
             seed,
+            expiry,
+            taker,
+            mode,
+            collection,
+            price_feed,
+            spread_bps,
+            hashlock,
+            min_fill,
         })
     }
 }
@@ -109,6 +222,44 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Make<'a> {
         let instruction_data = MakeInstructionData::try_from(data)?;
         let accounts = MakeAccounts::try_from(accounts)?;
 
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        if instruction_data.expiry.ne(&0) && instruction_data.expiry <= Clock::get()?.unix_timestamp
+        {
+            return Err(EscrowError::InvalidExpiry.into());
+        }
+
+        if instruction_data.mode & NFT_TOKEN_A != 0 {
+            NftMintAccount::check(accounts.a_mint)?;
+        }
+
+        if instruction_data.mode & NFT_TOKEN_B != 0 {
+            NftMintAccount::check(accounts.b_mint)?;
+        }
+
+        if instruction_data.collection.ne(&Pubkey::default()) {
+            let metadata_a = accounts
+                .trailing
+                .metadata_a
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            if MetadataAccount::verified_collection(metadata_a, accounts.a_mint)?
+                .ne(&Some(instruction_data.collection))
+            {
+                return Err(EscrowError::InvalidCollection.into());
+            }
+        }
+
+        if let Some(market) = accounts.trailing.market {
+            if !Market::load(&market.try_borrow_data()?)?
+                .is_pair(accounts.a_mint.key(), accounts.b_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
         let (_, bump) = find_program_address(
             &[
                 b"escrow",
@@ -128,7 +279,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Make<'a> {
             Seed::from(&bump_binding),
         ];
 
-        ProgramAccount::init(accounts.maker, accounts.escrow, &seeds, Escrow::LEN)?;
+        ProgramAccount::init(accounts.maker, accounts.escrow, &seeds, Escrow::SPACE)?;
 
         AssociatedTokenAccount::init(
             accounts.maker,
@@ -152,7 +303,11 @@ impl<'a> Make<'a> {
 
     pub fn process(&mut self) -> ProgramResult {
         let mut data = self.accounts.escrow.try_borrow_mut_data()?;
-        let escrow = Escrow::load_mut(data.as_mut())?;
+        let escrow = Escrow::init(data.as_mut())?;
+
+        if let Some(market) = self.accounts.trailing.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?.record_open()?;
+        }
 
         escrow.set_inner(
             *self.accounts.maker.key(),
@@ -162,6 +317,15 @@ impl<'a> Make<'a> {
             self.instruction_data.receive,
             self.bump,
         );
+        escrow.set_expiry(self.instruction_data.expiry);
+        escrow.set_taker(self.instruction_data.taker);
+        escrow.set_oracle(
+            self.instruction_data.price_feed,
+            self.instruction_data.spread_bps,
+        );
+        escrow.set_hashlock(self.instruction_data.hashlock);
+        escrow.set_min_fill(self.instruction_data.min_fill);
+        escrow.set_indexed(self.accounts.trailing.market.is_some());
 
         Transfer {
             from: self.accounts.maker_ata,
@@ -0,0 +1,278 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{BasketEscrow, Config},
+};
+
+use super::{
+    AccountCheck, AssociateTokenAccountInit, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+    ConfigAccount, MintAccount, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct MakeBasketAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub basket: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    // `[mint, maker_ata, vault]` for each offered leg, then `[mint]` for each requested leg
+    pub legs: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MakeBasketAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, basket, config, system_program, token_program, _associated_token_program, legs @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+        ConfigAccount::check(config)?;
+
+        Ok(Self {
+            maker,
+            basket,
+            config,
+            system_program,
+            token_program,
+            legs,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct MakeBasketInstructionData {
+    pub seed: u64,
+    pub expiry: i64, // 0 => never expires
+    pub offer_count: u8,
+    pub request_count: u8,
+    pub amounts: [u64; BasketEscrow::MAX_LEGS * 2], // offered amounts, then requested amounts
+}
+
+impl<'a> TryFrom<&'a [u8]> for MakeBasketInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const HEADER: usize = size_of::<u64>() + size_of::<i64>() + size_of::<u8>() * 2;
+
+        if data.len() < HEADER {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let expiry = i64::from_le_bytes(data[8..16].try_into().unwrap());
+        let offer_count = data[16];
+        let request_count = data[17];
+
+        if !(1..=BasketEscrow::MAX_LEGS).contains(&(offer_count as usize))
+            || !(1..=BasketEscrow::MAX_LEGS).contains(&(request_count as usize))
+        {
+            return Err(EscrowError::InvalidLegs.into());
+        }
+
+        let count = offer_count as usize + request_count as usize;
+        if data.len() != HEADER + count * size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut amounts = [0u64; BasketEscrow::MAX_LEGS * 2];
+        for (amount, bytes) in amounts
+            .iter_mut()
+            .zip(data[HEADER..].chunks_exact(size_of::<u64>()))
+        {
+            *amount = u64::from_le_bytes(bytes.try_into().unwrap());
+
+            if (*amount).eq(&0) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+
+        Ok(Self {
+            seed,
+            expiry,
+            offer_count,
+            request_count,
+            amounts,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct MakeBasket<'a> {
+    pub accounts: MakeBasketAccounts<'a>,
+    pub instruction_data: MakeBasketInstructionData,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MakeBasket<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MakeBasketInstructionData::try_from(data)?;
+        let accounts = MakeBasketAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        if instruction_data.expiry.ne(&0) && instruction_data.expiry <= Clock::get()?.unix_timestamp
+        {
+            return Err(EscrowError::InvalidExpiry.into());
+        }
+
+        let offer_count = instruction_data.offer_count as usize;
+        let request_count = instruction_data.request_count as usize;
+
+        if accounts.legs.len() != offer_count * 3 + request_count {
+            return Err(EscrowError::InvalidLegs.into());
+        }
+
+        let (offered, requested) = accounts.legs.split_at(offer_count * 3);
+
+        // Repeated offered mints would share a vault, repeated requested mints are just
+        // confusing. Either way, reject them.
+        for (i, mint) in requested.iter().enumerate() {
+            MintAccount::check(mint)?;
+
+            if requested[..i]
+                .iter()
+                .any(|other| other.key().eq(mint.key()))
+            {
+                return Err(EscrowError::InvalidLegs.into());
+            }
+        }
+
+        let (_, bump) = find_program_address(
+            &[
+                b"basket",
+                accounts.maker.key(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+
+        let seeds = [
+            Seed::from(b"basket"),
+            Seed::from(accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init(
+            accounts.maker,
+            accounts.basket,
+            &seeds,
+            BasketEscrow::space(offer_count, request_count),
+        )?;
+
+        for (i, leg) in offered.chunks_exact(3).enumerate() {
+            let [mint, maker_ata, vault] = leg else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            MintAccount::check(mint)?;
+            AssociatedTokenAccount::check(maker_ata, mint, accounts.maker, accounts.token_program)?;
+
+            if offered[..i * 3]
+                .chunks_exact(3)
+                .any(|other| other[0].key().eq(mint.key()))
+            {
+                return Err(EscrowError::InvalidLegs.into());
+            }
+
+            AssociatedTokenAccount::init(
+                accounts.maker,
+                vault,
+                accounts.basket,
+                mint,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> MakeBasket<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let offer_count = self.instruction_data.offer_count as usize;
+        let (offered, requested) = self.accounts.legs.split_at(offer_count * 3);
+
+        {
+            let mut data = self.accounts.basket.try_borrow_mut_data()?;
+
+            BasketEscrow::init(data.as_mut())?.set_inner(
+                *self.accounts.maker.key(),
+                self.instruction_data.seed,
+                self.instruction_data.expiry,
+                self.instruction_data.offer_count,
+                self.instruction_data.request_count,
+                self.bump,
+            );
+
+            let mints = offered
+                .chunks_exact(3)
+                .map(|leg| &leg[0])
+                .chain(requested.iter());
+
+            for ((leg, mint), amount) in BasketEscrow::legs_mut(data.as_mut())?
+                .iter_mut()
+                .zip(mints)
+                .zip(self.instruction_data.amounts)
+            {
+                leg.mint = *mint.key();
+                leg.set_amount(amount);
+            }
+        }
+
+        // Maker -> Vaults
+        for (leg, amount) in offered.chunks_exact(3).zip(self.instruction_data.amounts) {
+            Transfer {
+                from: &leg[1],
+                to: &leg[2],
+                authority: self.accounts.maker,
+                amount,
+            }
+            .invoke()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, MilestoneEscrow},
+};
+
+use super::{
+    AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount, ProgramAccount,
+    ProgramAccountInit,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct MakeMilestoneAccounts<'a> {
+    #[signer]
+    pub payer: &'a AccountInfo,
+    pub escrow: &'a AccountInfo, // `[b"milestone", payer, seed]` PDA
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = payer)]
+    pub payer_ata: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct MakeMilestoneInstructionData {
+    pub seed: u64,
+    pub deadline: i64,
+    pub payee: Pubkey,
+    pub authority: Pubkey,
+    pub arbiter: Pubkey, // zeroed for no arbiter
+    pub milestone_count: u8,
+    pub amounts: [u64; MilestoneEscrow::MAX_MILESTONES],
+}
+
+impl<'a> TryFrom<&'a [u8]> for MakeMilestoneInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const HEADER: usize =
+            size_of::<u64>() + size_of::<i64>() + size_of::<Pubkey>() * 3 + size_of::<u8>();
+
+        if data.len() < HEADER {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let deadline = i64::from_le_bytes(data[8..16].try_into().unwrap());
+        let payee: Pubkey = data[16..48].try_into().unwrap();
+        let authority: Pubkey = data[48..80].try_into().unwrap();
+        let arbiter: Pubkey = data[80..112].try_into().unwrap();
+        let milestone_count = data[112];
+
+        if !(1..=MilestoneEscrow::MAX_MILESTONES).contains(&(milestone_count as usize)) {
+            return Err(EscrowError::InvalidMilestones.into());
+        }
+
+        if data.len() != HEADER + milestone_count as usize * size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut amounts = [0u64; MilestoneEscrow::MAX_MILESTONES];
+        for (amount, bytes) in amounts
+            .iter_mut()
+            .zip(data[HEADER..].chunks_exact(size_of::<u64>()))
+        {
+            *amount = u64::from_le_bytes(bytes.try_into().unwrap());
+
+            if (*amount).eq(&0) {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+
+        Ok(Self {
+            seed,
+            deadline,
+            payee,
+            authority,
+            arbiter,
+            milestone_count,
+            amounts,
+        })
+    }
+}
+
+impl MakeMilestoneInstructionData {
+    #[inline(always)]
+    pub fn amounts(&self) -> &[u64] {
+        &self.amounts[..self.milestone_count as usize]
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct MakeMilestone<'a> {
+    pub accounts: MakeMilestoneAccounts<'a>,
+    pub instruction_data: MakeMilestoneInstructionData,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MakeMilestone<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MakeMilestoneInstructionData::try_from(data)?;
+        let accounts = MakeMilestoneAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        if instruction_data.deadline <= Clock::get()?.unix_timestamp {
+            return Err(EscrowError::InvalidExpiry.into());
+        }
+
+        let (_, bump) = find_program_address(
+            &[
+                b"milestone",
+                accounts.payer.key(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+
+        let seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(accounts.payer.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init(
+            accounts.payer,
+            accounts.escrow,
+            &seeds,
+            MilestoneEscrow::space(instruction_data.milestone_count as usize),
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.payer,
+            accounts.vault,
+            accounts.escrow,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> MakeMilestone<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+
+            let escrow = MilestoneEscrow::init(data.as_mut())?;
+            escrow.set_inner(
+                *self.accounts.payer.key(),
+                self.instruction_data.payee,
+                *self.accounts.mint.key(),
+                self.instruction_data.authority,
+                self.instruction_data.seed,
+                self.bump,
+            );
+            escrow.set_arbiter(self.instruction_data.arbiter);
+            escrow.set_deadline(self.instruction_data.deadline);
+            escrow.set_milestone_count(self.instruction_data.milestone_count);
+
+            for (index, amount) in self.instruction_data.amounts().iter().enumerate() {
+                MilestoneEscrow::set_amount(data.as_mut(), index, *amount)?;
+            }
+        }
+
+        let total = self
+            .instruction_data
+            .amounts()
+            .iter()
+            .try_fold(0u64, |total, amount| total.checked_add(*amount))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // Payer -> Vault, every tranche up front
+        Transfer {
+            from: self.accounts.payer_ata,
+            to: self.accounts.vault,
+            authority: self.accounts.payer,
+            amount: total,
+        }
+        .invoke()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, StreamEscrow},
+};
+
+use super::{
+    AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount, ProgramAccount,
+    ProgramAccountInit,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct MakeStreamAccounts<'a> {
+    #[signer]
+    pub sender: &'a AccountInfo,
+    pub escrow: &'a AccountInfo, // `[b"stream", sender, seed]` PDA
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = sender)]
+    pub sender_ata: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct MakeStreamInstructionData {
+    pub seed: u64,
+    pub recipient: Pubkey,
+    pub rate: u64,
+    pub deposit: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for MakeStreamInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 3 + size_of::<Pubkey>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let recipient: Pubkey = data[8..40].try_into().unwrap();
+        let rate = u64::from_le_bytes(data[40..48].try_into().unwrap());
+        let deposit = u64::from_le_bytes(data[48..56].try_into().unwrap());
+
+        if rate.eq(&0) || deposit.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            seed,
+            recipient,
+            rate,
+            deposit,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Starts streaming the deposit to the recipient from the current `Clock` time.
+pub struct MakeStream<'a> {
+    pub accounts: MakeStreamAccounts<'a>,
+    pub instruction_data: MakeStreamInstructionData,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MakeStream<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MakeStreamInstructionData::try_from(data)?;
+        let accounts = MakeStreamAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let (_, bump) = find_program_address(
+            &[
+                b"stream",
+                accounts.sender.key(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+
+        let seeds = [
+            Seed::from(b"stream"),
+            Seed::from(accounts.sender.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init(
+            accounts.sender,
+            accounts.escrow,
+            &seeds,
+            StreamEscrow::SPACE,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.sender,
+            accounts.vault,
+            accounts.escrow,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> MakeStream<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+
+            let escrow = StreamEscrow::init(data.as_mut())?;
+            escrow.set_inner(
+                *self.accounts.sender.key(),
+                self.instruction_data.recipient,
+                *self.accounts.mint.key(),
+                self.instruction_data.seed,
+                self.bump,
+            );
+            escrow.set_stream(
+                Clock::get()?.unix_timestamp,
+                self.instruction_data.rate,
+                self.instruction_data.deposit,
+            );
+        }
+
+        // Sender -> Vault
+        Transfer {
+            from: self.accounts.sender_ata,
+            to: self.accounts.vault,
+            authority: self.accounts.sender,
+            amount: self.instruction_data.deposit,
+        }
+        .invoke()?;
+
+        Ok(())
+    }
+}
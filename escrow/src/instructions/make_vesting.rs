@@ -0,0 +1,192 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, VestingEscrow},
+};
+
+use super::{
+    AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount, ProgramAccount,
+    ProgramAccountInit,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct MakeVestingAccounts<'a> {
+    #[signer]
+    pub funder: &'a AccountInfo,
+    pub escrow: &'a AccountInfo, // `[b"vesting", funder, seed]` PDA
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = funder)]
+    pub funder_ata: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct MakeVestingInstructionData {
+    pub seed: u64,
+    pub beneficiary: Pubkey,
+    pub start: i64,
+    pub cliff: i64,
+    pub end: i64,
+    pub total: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for MakeVestingInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 + size_of::<Pubkey>() + size_of::<i64>() * 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let beneficiary: Pubkey = data[8..40].try_into().unwrap();
+        let start = i64::from_le_bytes(data[40..48].try_into().unwrap());
+        let cliff = i64::from_le_bytes(data[48..56].try_into().unwrap());
+        let end = i64::from_le_bytes(data[56..64].try_into().unwrap());
+        let total = u64::from_le_bytes(data[64..72].try_into().unwrap());
+
+        if total.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if start >= end || cliff < start || cliff > end {
+            return Err(EscrowError::InvalidSchedule.into());
+        }
+
+        Ok(Self {
+            seed,
+            beneficiary,
+            start,
+            cliff,
+            end,
+            total,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct MakeVesting<'a> {
+    pub accounts: MakeVestingAccounts<'a>,
+    pub instruction_data: MakeVestingInstructionData,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for MakeVesting<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = MakeVestingInstructionData::try_from(data)?;
+        let accounts = MakeVestingAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let (_, bump) = find_program_address(
+            &[
+                b"vesting",
+                accounts.funder.key(),
+                &instruction_data.seed.to_le_bytes(),
+            ],
+            &crate::ID,
+        );
+
+        let seed_binding = instruction_data.seed.to_le_bytes();
+        let bump_binding = [bump];
+
+        let seeds = [
+            Seed::from(b"vesting"),
+            Seed::from(accounts.funder.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump_binding),
+        ];
+
+        ProgramAccount::init(
+            accounts.funder,
+            accounts.escrow,
+            &seeds,
+            VestingEscrow::SPACE,
+        )?;
+
+        AssociatedTokenAccount::init(
+            accounts.funder,
+            accounts.vault,
+            accounts.escrow,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump: bump_binding,
+        })
+    }
+}
+
+impl<'a> MakeVesting<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    pub fn process(&mut self) -> ProgramResult {
+        {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+
+            let escrow = VestingEscrow::init(data.as_mut())?;
+            escrow.set_inner(
+                *self.accounts.funder.key(),
+                self.instruction_data.beneficiary,
+                *self.accounts.mint.key(),
+                self.instruction_data.seed,
+                self.instruction_data.total,
+                self.bump,
+            );
+            escrow.set_schedule(
+                self.instruction_data.start,
+                self.instruction_data.cliff,
+                self.instruction_data.end,
+            );
+        }
+
+        // Funder -> Vault
+        Transfer {
+            from: self.accounts.funder_ata,
+            to: self.accounts.vault,
+            authority: self.accounts.funder,
+            amount: self.instruction_data.total,
+        }
+        .invoke()?;
+
+        Ok(())
+    }
+}
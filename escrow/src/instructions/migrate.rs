@@ -0,0 +1,108 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Escrow, EscrowV0},
+};
+
+use super::ProgramAccount;
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct MigrateAccounts<'a> {
+    #[signer]
+    pub maker: &'a AccountInfo, // pays for the extra space
+    #[check(ProgramAccount)]
+    pub escrow: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Reallocates an escrow created before the account header to the current layout.
+pub struct Migrate<'a> {
+    pub accounts: MigrateAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Migrate<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = MigrateAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+
+            if Escrow::version(&data)?.eq(&Escrow::VERSION) {
+                return Err(EscrowError::AlreadyMigrated.into());
+            }
+
+            let escrow = EscrowV0::load(&data)?;
+
+            // Legacy escrows have no header, their address deriving from the maker is what
+            // tells them apart from any other account of the same size. It also checks that the
+            // signer is the escrow's maker.
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.maker.ne(accounts.maker.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Migrate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    // Not gated by `Config::paused`, it moves no tokens.
+    pub fn process(&mut self) -> ProgramResult {
+        let version = Escrow::version(&self.accounts.escrow.try_borrow_data()?)?;
+
+        let lamports = Rent::get()?
+            .minimum_balance(Escrow::SPACE)
+            .saturating_sub(self.accounts.escrow.lamports());
+
+        // Maker -> Escrow, topping the rent up for the new size
+        if lamports.gt(&0) {
+            Transfer {
+                from: self.accounts.maker,
+                to: self.accounts.escrow,
+                lamports,
+            }
+            .invoke()?;
+        }
+
+        self.accounts.escrow.resize(Escrow::SPACE)?;
+
+        Escrow::migrate(
+            self.accounts.escrow.try_borrow_mut_data()?.as_mut(),
+            version,
+        )
+    }
+}
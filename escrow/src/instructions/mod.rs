@@ -1,5 +1,92 @@
 pub mod make;
 pub use make::*;
 
+pub mod take;
+pub use take::*;
+
+pub mod update;
+pub use update::*;
+
+pub mod refund;
+pub use refund::*;
+
+pub mod close_expired;
+pub use close_expired::*;
+
+pub mod init_config;
+pub use init_config::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod set_paused;
+pub use set_paused::*;
+
+pub mod make_basket;
+pub use make_basket::*;
+
+pub mod take_basket;
+pub use take_basket::*;
+
+pub mod refund_basket;
+pub use refund_basket::*;
+
+pub mod fill_order;
+pub use fill_order::*;
+
+pub mod take_many;
+pub use take_many::*;
+
+pub mod propose_counter;
+pub use propose_counter::*;
+
+pub mod accept_counter;
+pub use accept_counter::*;
+
+pub mod withdraw_counter;
+pub use withdraw_counter::*;
+
+pub mod make_milestone;
+pub use make_milestone::*;
+
+pub mod release_milestone;
+pub use release_milestone::*;
+
+pub mod reclaim_milestones;
+pub use reclaim_milestones::*;
+
+pub mod dispute;
+pub use dispute::*;
+
+pub mod resolve;
+pub use resolve::*;
+
+pub mod make_vesting;
+pub use make_vesting::*;
+
+pub mod claim;
+pub use claim::*;
+
+pub mod make_stream;
+pub use make_stream::*;
+
+pub mod withdraw_stream;
+pub use withdraw_stream::*;
+
+pub mod cancel_stream;
+pub use cancel_stream::*;
+
+pub mod redeem;
+pub use redeem::*;
+
+pub mod init_market;
+pub use init_market::*;
+
+pub mod migrate;
+pub use migrate::*;
+
+pub mod register_referral;
+pub use register_referral::*;
+
 pub mod helpers;
 pub use helpers::*;
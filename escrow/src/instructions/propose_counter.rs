@@ -0,0 +1,212 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, CounterOffer, Escrow},
+};
+
+use super::{
+    AccountCheck, AssociateTokenAccountInit, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+    ConfigAccount, MintAccount, ProgramAccount, ProgramAccountInit, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct ProposeCounterAccounts<'a> {
+    pub proposer: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+    pub counter: &'a AccountInfo, // `[b"counter", escrow, proposer]` PDA
+    pub b_mint: &'a AccountInfo,
+    pub proposer_ata_b: &'a AccountInfo,
+    pub counter_ata_b: &'a AccountInfo, // holds the deposit
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ProposeCounterAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [proposer, escrow, counter, b_mint, proposer_ata_b, counter_ata_b, config, system_program, token_program, _associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(proposer)?;
+        ProgramAccount::check(escrow)?;
+        MintAccount::check(b_mint)?;
+        AssociatedTokenAccount::check(proposer_ata_b, b_mint, proposer, token_program)?;
+        ConfigAccount::check(config)?;
+
+        Ok(Self {
+            proposer,
+            escrow,
+            counter,
+            b_mint,
+            proposer_ata_b,
+            counter_ata_b,
+            config,
+            system_program,
+            token_program,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct ProposeCounterInstructionData {
+    pub amount: u64,  // `token_a` requested from the vault
+    pub deposit: u64, // `token_b` offered for it
+}
+
+impl<'a> TryFrom<&'a [u8]> for ProposeCounterInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let deposit = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if amount.eq(&0) || deposit.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount, deposit })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct ProposeCounter<'a> {
+    pub accounts: ProposeCounterAccounts<'a>,
+    pub instruction_data: ProposeCounterInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ProposeCounter<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = ProposeCounterInstructionData::try_from(data)?;
+        let accounts = ProposeCounterAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            if escrow.token_b_mint.ne(accounts.b_mint.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.is_hashlocked() {
+                return Err(EscrowError::Hashlocked.into());
+            }
+
+            if escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferExpired.into());
+            }
+
+            if !escrow.can_be_taken_by(accounts.proposer.key()) {
+                return Err(EscrowError::InvalidTaker.into());
+            }
+
+            // A counter offer is a partial fill of the vault.
+            if instruction_data.amount.lt(&escrow.min_fill()) {
+                return Err(EscrowError::BelowMinFill.into());
+            }
+        }
+
+        let (counter_key, bump) = find_program_address(
+            &[b"counter", accounts.escrow.key(), accounts.proposer.key()],
+            &crate::ID,
+        );
+        if counter_key.ne(accounts.counter.key()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let bump_binding = [bump];
+        let seeds = [
+            Seed::from(b"counter"),
+            Seed::from(accounts.escrow.key().as_ref()),
+            Seed::from(accounts.proposer.key().as_ref()),
+            Seed::from(&bump_binding),
+        ];
+
+        // Fails if the proposer already has an open counter offer on this escrow.
+        ProgramAccount::init(
+            accounts.proposer,
+            accounts.counter,
+            &seeds,
+            CounterOffer::SPACE,
+        )?;
+
+        {
+            let mut data = accounts.counter.try_borrow_mut_data()?;
+            CounterOffer::init(data.as_mut())?.set_inner(
+                *accounts.escrow.key(),
+                *accounts.proposer.key(),
+                instruction_data.amount,
+                instruction_data.deposit,
+                bump_binding,
+            );
+        }
+
+        AssociatedTokenAccount::init(
+            accounts.proposer,
+            accounts.counter_ata_b,
+            accounts.counter,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ProposeCounter<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Proposer -> Counter offer
+        Transfer {
+            from: self.accounts.proposer_ata_b,
+            to: self.accounts.counter_ata_b,
+            authority: self.accounts.proposer,
+            amount: self.instruction_data.deposit,
+        }
+        .invoke()?;
+
+        Ok(())
+    }
+}
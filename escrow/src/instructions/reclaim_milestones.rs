@@ -0,0 +1,141 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::MilestoneEscrow};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, MilestoneAccount,
+    ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct ReclaimMilestonesAccounts<'a> {
+    #[signer]
+    pub payer: &'a AccountInfo,
+    #[check(MilestoneAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub payer_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct ReclaimMilestones<'a> {
+    pub accounts: ReclaimMilestonesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReclaimMilestones<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ReclaimMilestonesAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+
+            // The escrow address is derived from the payer, so this also checks that the signer
+            // is the escrow's payer.
+            let escrow_key = create_program_address(
+                &[
+                    b"milestone",
+                    accounts.payer.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.mint.ne(accounts.mint.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.is_disputed() {
+                return Err(EscrowError::EscrowDisputed.into());
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.payer,
+            accounts.payer_ata,
+            accounts.payer,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ReclaimMilestones<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+
+    // Not gated by `Config::paused`, payers must always be able to exit after the deadline.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+
+            if !escrow.is_past_deadline(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferNotExpired.into());
+            }
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(self.accounts.payer.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?.amount();
+
+        // Vault -> Payer, every unreleased tranche
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.payer_ata,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.vault,
+            destination: self.accounts.payer,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.payer)?;
+
+        Ok(())
+    }
+}
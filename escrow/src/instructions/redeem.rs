@@ -0,0 +1,193 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Escrow, Market},
+};
+
+use super::{
+    sha256, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount,
+    MarketAccount, MarketTrailingAccounts, ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct RedeemAccounts<'a> {
+    #[signer]
+    pub payer: &'a AccountInfo, // anyone, only pays for `taker_ata_a` if it does not exist
+    pub maker: &'a AccountInfo, // gets the rent back
+    pub taker: &'a AccountInfo,
+    #[check(ProgramAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub a_mint: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = escrow)]
+    pub escrow_ata: &'a AccountInfo, // vault
+    pub taker_ata_a: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    #[remaining]
+    pub trailing: MarketTrailingAccounts<'a>,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct RedeemInstructionData<'a> {
+    pub preimage: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for RedeemInstructionData<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { preimage: data })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Releases a hashlocked escrow's vault to its designated taker. Anyone holding the preimage
+/// may submit it, the tokens only ever go to the taker.
+pub struct Redeem<'a> {
+    pub accounts: RedeemAccounts<'a>,
+    pub instruction_data: RedeemInstructionData<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Redeem<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = RedeemInstructionData::try_from(data)?;
+        let accounts = RedeemAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.token_a_mint.ne(accounts.a_mint.key())
+                || escrow.taker.ne(accounts.taker.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            MarketAccount::check_escrow(accounts.trailing.market, escrow)?;
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.payer,
+            accounts.taker_ata_a,
+            accounts.taker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Redeem<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    pub fn process(&mut self) -> ProgramResult {
+        if Config::load(&self.accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            if !escrow.is_hashlocked()
+                || sha256(self.instruction_data.preimage).ne(&escrow.hashlock)
+            {
+                return Err(EscrowError::InvalidPreimage.into());
+            }
+
+            if escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferExpired.into());
+            }
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                .amount();
+
+        // Vault -> Taker
+        Transfer {
+            from: self.accounts.escrow_ata,
+            to: self.accounts.taker_ata_a,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.escrow_ata,
+            destination: self.accounts.maker,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        // The taker paid on another chain, there is no `mint_b` volume to record.
+        if let Some(market) = self.accounts.trailing.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?.record_fill(amount, 0)?;
+        }
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Escrow, Market},
+};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, MarketAccount,
+    MarketTrailingAccounts, ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct RefundAccounts<'a> {
+    #[signer]
+    pub maker: &'a AccountInfo,
+    #[check(ProgramAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub a_mint: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = escrow)]
+    pub escrow_ata: &'a AccountInfo, // vault
+    pub maker_ata_a: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    #[remaining]
+    pub trailing: MarketTrailingAccounts<'a>,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct Refund<'a> {
+    pub accounts: RefundAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Refund<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = RefundAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            // The escrow address is derived from the maker, so this also checks that the signer
+            // is the escrow's maker.
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.token_a_mint.ne(accounts.a_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            MarketAccount::check_escrow(accounts.trailing.market, escrow)?;
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.maker,
+            accounts.maker_ata_a,
+            accounts.maker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Refund<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    // Deliberately not gated by `Config::paused`, makers must always be able to exit.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            // The taker may be relying on the hashlock until the timeout.
+            if escrow.is_hashlocked() && !escrow.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::Hashlocked.into());
+            }
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                .amount();
+
+        // Vault -> Maker
+        Transfer {
+            from: self.accounts.escrow_ata,
+            to: self.accounts.maker_ata_a,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.escrow_ata,
+            destination: self.accounts.maker,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        if let Some(market) = self.accounts.trailing.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?.record_close()?;
+        }
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
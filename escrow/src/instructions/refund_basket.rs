@@ -0,0 +1,170 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::BasketEscrow};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, BasketAccount, ProgramAccount, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct RefundBasketAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub basket: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    // `[mint, vault, maker_ata]` for each offered leg
+    pub legs: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RefundBasketAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, basket, system_program, token_program, _associated_token_program, legs @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(maker)?;
+        BasketAccount::check(basket)?;
+
+        Ok(Self {
+            maker,
+            basket,
+            system_program,
+            token_program,
+            legs,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct RefundBasket<'a> {
+    pub accounts: RefundBasketAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RefundBasket<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = RefundBasketAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.basket.try_borrow_data()?;
+            let basket = BasketEscrow::load(&data)?;
+
+            // The basket address is derived from the maker, so this also checks that the signer
+            // is the basket's maker.
+            let basket_key = create_program_address(
+                &[
+                    b"basket",
+                    accounts.maker.key(),
+                    &basket.seed().to_le_bytes(),
+                    &basket.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if basket_key.ne(accounts.basket.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let (offered_legs, _) = BasketEscrow::legs(&data)?;
+
+            if accounts.legs.len() != offered_legs.len() * 3 {
+                return Err(EscrowError::InvalidLegs.into());
+            }
+
+            for (leg, accounts_leg) in offered_legs.iter().zip(accounts.legs.chunks_exact(3)) {
+                let [mint, vault, maker_ata] = accounts_leg else {
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+
+                if leg.mint.ne(mint.key()) {
+                    return Err(EscrowError::InvalidLegs.into());
+                }
+
+                AssociatedTokenAccount::check(
+                    vault,
+                    mint,
+                    accounts.basket,
+                    accounts.token_program,
+                )?;
+                AssociatedTokenAccount::init_if_needed(
+                    accounts.maker,
+                    maker_ata,
+                    accounts.maker,
+                    mint,
+                    accounts.system_program,
+                    accounts.token_program,
+                )?;
+            }
+        }
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> RefundBasket<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    // Like `Refund`, deliberately not gated by `Config::paused`.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.basket.try_borrow_data()?;
+            let basket = BasketEscrow::load(&data)?;
+
+            (basket.seed(), basket.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"basket"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        // Vaults -> Maker
+        for leg in self.accounts.legs.chunks_exact(3) {
+            let amount = pinocchio_token::state::TokenAccount::from_account_info(&leg[1])?.amount();
+
+            Transfer {
+                from: &leg[1],
+                to: &leg[2],
+                authority: self.accounts.basket,
+                amount,
+            }
+            .invoke_signed(&signers)?;
+
+            CloseAccount {
+                account: &leg[1],
+                destination: self.accounts.maker,
+                authority: self.accounts.basket,
+            }
+            .invoke_signed(&signers)?;
+        }
+
+        ProgramAccount::close(self.accounts.basket, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
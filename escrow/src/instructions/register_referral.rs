@@ -0,0 +1,86 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo, instruction::Seed, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Referral},
+};
+
+use super::{ConfigAccount, ProgramAccount, ProgramAccountInit};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct RegisterReferralAccounts<'a> {
+    #[signer]
+    pub admin: &'a AccountInfo, // pays for the referral account
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub referrer: &'a AccountInfo,
+    #[mint]
+    pub b_mint: &'a AccountInfo,
+    #[pda(seeds = [b"referral", referrer.key(), b_mint.key()], bump = referral_bump)]
+    pub referral: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub referral_bump: [u8; 1],
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+/// Registers a partner as a referrer for `mint_b` fills, `Take` only shares fees with
+/// registered referrers.
+pub struct RegisterReferral<'a> {
+    pub accounts: RegisterReferralAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RegisterReferral<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = RegisterReferralAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?
+            .admin
+            .ne(accounts.admin.key())
+        {
+            return Err(EscrowError::Unauthorized.into());
+        }
+
+        let seeds = [
+            Seed::from(b"referral"),
+            Seed::from(accounts.referrer.key()),
+            Seed::from(accounts.b_mint.key()),
+            Seed::from(&accounts.referral_bump),
+        ];
+
+        ProgramAccount::init(accounts.admin, accounts.referral, &seeds, Referral::SPACE)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> RegisterReferral<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    // Not gated by `Config::paused`, it moves no tokens.
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.referral.try_borrow_mut_data()?;
+
+        Referral::init(data.as_mut())?.set_inner(
+            *self.accounts.referrer.key(),
+            *self.accounts.b_mint.key(),
+            self.accounts.referral_bump,
+        );
+
+        Ok(())
+    }
+}
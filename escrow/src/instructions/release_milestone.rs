@@ -0,0 +1,168 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, MilestoneEscrow},
+};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount,
+    MilestoneAccount, ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct ReleaseMilestoneAccounts<'a> {
+    #[signer]
+    pub authority: &'a AccountInfo,
+    pub payer: &'a AccountInfo, // gets the rent back after the last tranche
+    pub payee: &'a AccountInfo,
+    #[check(MilestoneAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub payee_ata: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct ReleaseMilestone<'a> {
+    pub accounts: ReleaseMilestoneAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReleaseMilestone<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ReleaseMilestoneAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"milestone",
+                    accounts.payer.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.payee.ne(accounts.payee.key())
+                || escrow.mint.ne(accounts.mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.authority.ne(accounts.authority.key()) {
+                return Err(EscrowError::Unauthorized.into());
+            }
+
+            if escrow.is_disputed() {
+                return Err(EscrowError::EscrowDisputed.into());
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.authority,
+            accounts.payee_ata,
+            accounts.payee,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ReleaseMilestone<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(&mut self) -> ProgramResult {
+        if Config::load(&self.accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let (seed, bump, amount, is_last) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+            let amount = MilestoneEscrow::amount(&data, escrow.released_count as usize)?;
+
+            (
+                escrow.seed(),
+                escrow.bump,
+                amount,
+                escrow.is_last_milestone(),
+            )
+        };
+
+        // The last tranche empties the vault, so that anything sent to it on top of the
+        // milestones doesn't keep it from closing.
+        let amount = match is_last {
+            true => pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?
+                .amount(),
+            false => amount,
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(self.accounts.payer.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        // Vault -> Payee
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.payee_ata,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        if is_last {
+            CloseAccount {
+                account: self.accounts.vault,
+                destination: self.accounts.payer,
+                authority: self.accounts.escrow,
+            }
+            .invoke_signed(&signers)?;
+
+            ProgramAccount::close(self.accounts.escrow, self.accounts.payer)?;
+        } else {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+            MilestoneEscrow::load_mut(data.as_mut())?.released_count += 1;
+        }
+
+        Ok(())
+    }
+}
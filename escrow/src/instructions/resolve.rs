@@ -0,0 +1,200 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::MilestoneEscrow};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, MilestoneAccount,
+    ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct ResolveAccounts<'a> {
+    #[signer]
+    pub arbiter: &'a AccountInfo,
+    pub payer: &'a AccountInfo, // gets the rent back
+    pub payee: &'a AccountInfo,
+    #[check(MilestoneAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub payer_ata: &'a AccountInfo,
+    pub payee_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct ResolveInstructionData {
+    pub payee_bps: u16, // share of the vault balance going to the payee, the rest goes back to the payer
+}
+
+impl<'a> TryFrom<&'a [u8]> for ResolveInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let payee_bps = u16::from_le_bytes(data.try_into().unwrap());
+
+        if payee_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { payee_bps })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct Resolve<'a> {
+    pub accounts: ResolveAccounts<'a>,
+    pub instruction_data: ResolveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Resolve<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = ResolveInstructionData::try_from(data)?;
+        let accounts = ResolveAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"milestone",
+                    accounts.payer.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.payee.ne(accounts.payee.key())
+                || escrow.mint.ne(accounts.mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if !escrow.is_disputed() {
+                return Err(EscrowError::NotDisputed.into());
+            }
+
+            if escrow.arbiter.ne(accounts.arbiter.key()) {
+                return Err(EscrowError::Unauthorized.into());
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.arbiter,
+            accounts.payer_ata,
+            accounts.payer,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.arbiter,
+            accounts.payee_ata,
+            accounts.payee,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Resolve<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    // Not gated by `Config::paused`, it is the only way out of a disputed escrow.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = MilestoneEscrow::load(&data)?;
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"milestone"),
+            Seed::from(self.accounts.payer.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let balance =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?.amount();
+
+        // Can't overflow, `payee_bps` is at most 10_000
+        let payee_amount =
+            (balance as u128 * self.instruction_data.payee_bps as u128 / 10_000) as u64;
+        let payer_amount = balance - payee_amount;
+
+        // Vault -> Payee
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.payee_ata,
+            authority: self.accounts.escrow,
+            amount: payee_amount,
+        }
+        .invoke_signed(&signers)?;
+
+        // Vault -> Payer
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.payer_ata,
+            authority: self.accounts.escrow,
+            amount: payer_amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.vault,
+            destination: self.accounts.payer,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.payer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::Config};
+
+use super::{AccountCheck, ConfigAccount, SignerAccount};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct SetPausedAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetPausedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(admin)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .admin
+            .ne(admin.key())
+        {
+            return Err(EscrowError::Unauthorized.into());
+        }
+
+        Ok(Self { admin, config })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct SetPausedInstructionData {
+    pub paused: bool,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetPausedInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let paused = match data {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(Self { paused })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct SetPaused<'a> {
+    pub accounts: SetPausedAccounts<'a>,
+    pub instruction_data: SetPausedInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for SetPaused<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = SetPausedInstructionData::try_from(data)?;
+        let accounts = SetPausedAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPaused<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+        Config::load_mut(data.as_mut())?.set_paused(self.instruction_data.paused);
+
+        Ok(())
+    }
+}
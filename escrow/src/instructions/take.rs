@@ -0,0 +1,415 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::{
+    instructions::{CloseAccount, Transfer},
+    state::Mint,
+};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Escrow, Market, Referral},
+};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ConfigAccount,
+    MarketAccount, PriceFeedAccount, ProgramAccount, ReferralAccount, TokenAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct TakeAccounts<'a> {
+    #[signer]
+    pub taker: &'a AccountInfo,
+    pub maker: &'a AccountInfo,
+    #[check(ProgramAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub a_mint: &'a AccountInfo,
+    #[mint]
+    pub b_mint: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = escrow)]
+    pub escrow_ata: &'a AccountInfo, // vault
+    pub taker_ata_a: &'a AccountInfo,
+    #[ata(mint = b_mint, authority = taker)]
+    pub taker_ata_b: &'a AccountInfo,
+    pub maker_ata_b: &'a AccountInfo,
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub fee_recipient: &'a AccountInfo, // must be the config's
+    pub fee_recipient_ata_b: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    #[remaining]
+    pub trailing: TakeTrailingAccounts<'a>,
+}
+
+/// Optional trailing `[referrer_ata_b, referral]`, `[price_feed]` and `[market]`, in that order.
+pub struct TakeTrailingAccounts<'a> {
+    pub referral: Option<TakeReferralAccounts<'a>>,
+    pub price_feed: Option<&'a AccountInfo>, // required by oracle-priced offers
+    pub market: Option<&'a AccountInfo>,     // required by indexed offers
+}
+
+/// Passed when the fill was routed by a referrer.
+pub struct TakeReferralAccounts<'a> {
+    pub referrer_ata_b: &'a AccountInfo, // any `mint_b` token account, its owner is the referrer
+    pub referral: &'a AccountInfo,       // registered by the admin, see `RegisterReferral`
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TakeTrailingAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let (accounts, market) = MarketAccount::split_last(accounts);
+        let (referral, price_feed) = match accounts {
+            [] => (None, None),
+            [price_feed] => (None, Some(price_feed)),
+            [referrer_ata_b, referral, price_feed @ ..] if price_feed.len() <= 1 => {
+                TokenAccount::check(referrer_ata_b)?;
+
+                (
+                    Some(TakeReferralAccounts {
+                        referrer_ata_b,
+                        referral,
+                    }),
+                    price_feed.first(),
+                )
+            }
+            _ => return Err(ProgramError::NotEnoughAccountKeys),
+        };
+
+        Ok(Self {
+            referral,
+            price_feed,
+            market,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct TakeInstructionData {
+    pub max_pay: u64,     // most `token_b` the taker pays, `u64::MAX` when omitted
+    pub min_receive: u64, // least `token_a` the taker accepts, `0` when omitted
+}
+
+impl<'a> TryFrom<&'a [u8]> for TakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // The slippage limits are optional, but have to be passed together.
+        match data.len() {
+            0 => Ok(Self {
+                max_pay: u64::MAX,
+                min_receive: 0,
+            }),
+            len if len == size_of::<u64>() * 2 => Ok(Self {
+                max_pay: u64::from_le_bytes(data[0..8].try_into().unwrap()),
+                min_receive: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct Take<'a> {
+    pub accounts: TakeAccounts<'a>,
+    pub instruction_data: TakeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Take<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = TakeInstructionData::try_from(data)?;
+        let accounts = TakeAccounts::try_from(accounts)?;
+
+        if Config::load(&accounts.config.try_borrow_data()?)?
+            .fee_recipient
+            .ne(accounts.fee_recipient.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key())
+                || escrow.token_a_mint.ne(accounts.a_mint.key())
+                || escrow.token_b_mint.ne(accounts.b_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            MarketAccount::check_escrow(accounts.trailing.market, escrow)?;
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.taker_ata_a,
+            accounts.taker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.maker_ata_b,
+            accounts.maker,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.fee_recipient_ata_b,
+            accounts.fee_recipient,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        if let Some(referral) = &accounts.trailing.referral {
+            Self::check_referral(accounts.taker, accounts.maker, accounts.b_mint, referral)?;
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Take<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &1;
+
+    /// Validates the referrer's token account and its `[b"referral", referrer, mint_b]` PDA,
+    /// which must have been registered by the admin. Neither party of the fill can refer it.
+    fn check_referral(
+        taker: &AccountInfo,
+        maker: &AccountInfo,
+        b_mint: &AccountInfo,
+        accounts: &TakeReferralAccounts,
+    ) -> ProgramResult {
+        let referrer = {
+            let referrer_ata_b =
+                pinocchio_token::state::TokenAccount::from_account_info(accounts.referrer_ata_b)?;
+
+            if referrer_ata_b.mint().ne(b_mint.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            *referrer_ata_b.owner()
+        };
+
+        if referrer.eq(taker.key()) || referrer.eq(maker.key()) {
+            return Err(EscrowError::InvalidReferrer.into());
+        }
+
+        ReferralAccount::check(accounts.referral)?;
+
+        let data = accounts.referral.try_borrow_data()?;
+        let referral = Referral::load(&data)?;
+
+        let referral_key = create_program_address(
+            &[b"referral", &referrer, b_mint.key(), &referral.bump],
+            &crate::ID,
+        )?;
+
+        if referral_key.ne(accounts.referral.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    /// Prices the vault's `amount` of `mint_a` in `mint_b` off the escrow's price feed.
+    fn quote(&self, escrow: &Escrow, amount: u64, now: i64) -> Result<u64, ProgramError> {
+        let price_feed = self
+            .accounts
+            .trailing
+            .price_feed
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if escrow.price_feed.ne(price_feed.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (price, exponent) = PriceFeedAccount::price(price_feed, now)?;
+
+        escrow.quote(
+            amount,
+            price,
+            exponent,
+            Mint::from_account_info(self.accounts.a_mint)?.decimals(),
+            Mint::from_account_info(self.accounts.b_mint)?.decimals(),
+        )
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                .amount();
+
+        let (seed, receive, bump) = {
+            let data = self.accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            if Config::load(&self.accounts.config.try_borrow_data()?)?.is_paused() {
+                return Err(EscrowError::ProgramPaused.into());
+            }
+
+            if escrow.is_hashlocked() {
+                return Err(EscrowError::Hashlocked.into());
+            }
+
+            let now = Clock::get()?.unix_timestamp;
+
+            if escrow.is_expired(now) {
+                return Err(EscrowError::OfferExpired.into());
+            }
+
+            if !escrow.can_be_taken_by(self.accounts.taker.key()) {
+                return Err(EscrowError::InvalidTaker.into());
+            }
+
+            let receive = if escrow.is_oracle_priced() {
+                self.quote(escrow, amount, now)?
+            } else {
+                escrow.receive()
+            };
+
+            (escrow.seed(), receive, escrow.bump)
+        };
+
+        // The maker can re-price or withdraw from the escrow, and an oracle price can move,
+        // between the taker signing and the take landing.
+        if receive.gt(&self.instruction_data.max_pay) {
+            return Err(EscrowError::MaxPayExceeded.into());
+        }
+
+        if amount.lt(&self.instruction_data.min_receive) {
+            return Err(EscrowError::MinReceiveNotMet.into());
+        }
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"escrow"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        // Vault -> Taker
+        Transfer {
+            from: self.accounts.escrow_ata,
+            to: self.accounts.taker_ata_a,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.escrow_ata,
+            destination: self.accounts.maker,
+            authority: self.accounts.escrow,
+        }
+        .invoke_signed(&signers)?;
+
+        // The protocol fee is taken out of what the maker receives, the taker always pays
+        // exactly `receive`. Routed fills split the fee with the referrer.
+        let (protocol_fee, referral_fee) = {
+            let data = self.accounts.config.try_borrow_data()?;
+            let config = Config::load(&data)?;
+            let fee = config.fee(receive)?;
+
+            match self.accounts.trailing.referral {
+                Some(_) => config.split_fee(fee)?,
+                None => (fee, 0),
+            }
+        };
+
+        // Taker -> Maker
+        Transfer {
+            from: self.accounts.taker_ata_b,
+            to: self.accounts.maker_ata_b,
+            authority: self.accounts.taker,
+            amount: receive
+                .checked_sub(protocol_fee + referral_fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        }
+        .invoke()?;
+
+        // Taker -> Fee recipient
+        if protocol_fee.gt(&0) {
+            Transfer {
+                from: self.accounts.taker_ata_b,
+                to: self.accounts.fee_recipient_ata_b,
+                authority: self.accounts.taker,
+                amount: protocol_fee,
+            }
+            .invoke()?;
+        }
+
+        // Taker -> Referrer
+        if let Some(referral) = &self.accounts.trailing.referral {
+            if referral_fee.gt(&0) {
+                Transfer {
+                    from: self.accounts.taker_ata_b,
+                    to: referral.referrer_ata_b,
+                    authority: self.accounts.taker,
+                    amount: referral_fee,
+                }
+                .invoke()?;
+            }
+
+            let mut data = referral.referral.try_borrow_mut_data()?;
+            Referral::load_mut(data.as_mut())?.record(receive, referral_fee)?;
+        }
+
+        if let Some(market) = self.accounts.trailing.market {
+            Market::load_mut(market.try_borrow_mut_data()?.as_mut())?
+                .record_fill(amount, receive)?;
+        }
+
+        ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
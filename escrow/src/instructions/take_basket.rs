@@ -0,0 +1,261 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{BasketEscrow, Config},
+};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, BasketAccount, ConfigAccount, ProgramAccount, SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct TakeBasketAccounts<'a> {
+    pub taker: &'a AccountInfo,
+    pub maker: &'a AccountInfo,
+    pub basket: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub fee_recipient: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    // `[mint, vault, taker_ata]` for each offered leg, then
+    // `[mint, taker_ata, maker_ata, fee_recipient_ata]` for each requested leg
+    pub legs: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TakeBasketAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [taker, maker, basket, config, fee_recipient, system_program, token_program, _associated_token_program, legs @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+        BasketAccount::check(basket)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .fee_recipient
+            .ne(fee_recipient.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            taker,
+            maker,
+            basket,
+            config,
+            fee_recipient,
+            system_program,
+            token_program,
+            legs,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct TakeBasket<'a> {
+    pub accounts: TakeBasketAccounts<'a>,
+    pub offer_count: usize,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TakeBasket<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = TakeBasketAccounts::try_from(accounts)?;
+
+        let data = accounts.basket.try_borrow_data()?;
+        let basket = BasketEscrow::load(&data)?;
+
+        let basket_key = create_program_address(
+            &[
+                b"basket",
+                accounts.maker.key(),
+                &basket.seed().to_le_bytes(),
+                &basket.bump,
+            ],
+            &crate::ID,
+        )?;
+
+        if basket_key.ne(accounts.basket.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (offered_legs, requested_legs) = BasketEscrow::legs(&data)?;
+
+        if accounts.legs.len() != offered_legs.len() * 3 + requested_legs.len() * 4 {
+            return Err(EscrowError::InvalidLegs.into());
+        }
+
+        let (offered, requested) = accounts.legs.split_at(offered_legs.len() * 3);
+
+        for (leg, accounts_leg) in offered_legs.iter().zip(offered.chunks_exact(3)) {
+            let [mint, vault, taker_ata] = accounts_leg else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            if leg.mint.ne(mint.key()) {
+                return Err(EscrowError::InvalidLegs.into());
+            }
+
+            AssociatedTokenAccount::check(vault, mint, accounts.basket, accounts.token_program)?;
+            AssociatedTokenAccount::init_if_needed(
+                accounts.taker,
+                taker_ata,
+                accounts.taker,
+                mint,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        for (leg, accounts_leg) in requested_legs.iter().zip(requested.chunks_exact(4)) {
+            let [mint, taker_ata, maker_ata, fee_recipient_ata] = accounts_leg else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            if leg.mint.ne(mint.key()) {
+                return Err(EscrowError::InvalidLegs.into());
+            }
+
+            AssociatedTokenAccount::check(taker_ata, mint, accounts.taker, accounts.token_program)?;
+            AssociatedTokenAccount::init_if_needed(
+                accounts.taker,
+                maker_ata,
+                accounts.maker,
+                mint,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+            AssociatedTokenAccount::init_if_needed(
+                accounts.taker,
+                fee_recipient_ata,
+                accounts.fee_recipient,
+                mint,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        let offer_count = offered_legs.len();
+        drop(data);
+
+        Ok(Self {
+            accounts,
+            offer_count,
+        })
+    }
+}
+
+impl<'a> TakeBasket<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let config_data = self.accounts.config.try_borrow_data()?;
+        let config = Config::load(&config_data)?;
+
+        if config.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let (seed, bump) = {
+            let data = self.accounts.basket.try_borrow_data()?;
+            let basket = BasketEscrow::load(&data)?;
+
+            if basket.is_expired(Clock::get()?.unix_timestamp) {
+                return Err(EscrowError::OfferExpired.into());
+            }
+
+            (basket.seed(), basket.bump)
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"basket"),
+            Seed::from(self.accounts.maker.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let (offered, requested) = self.accounts.legs.split_at(self.offer_count * 3);
+
+        // Vaults -> Taker
+        for leg in offered.chunks_exact(3) {
+            let amount = pinocchio_token::state::TokenAccount::from_account_info(&leg[1])?.amount();
+
+            Transfer {
+                from: &leg[1],
+                to: &leg[2],
+                authority: self.accounts.basket,
+                amount,
+            }
+            .invoke_signed(&signers)?;
+
+            CloseAccount {
+                account: &leg[1],
+                destination: self.accounts.maker,
+                authority: self.accounts.basket,
+            }
+            .invoke_signed(&signers)?;
+        }
+
+        {
+            let data = self.accounts.basket.try_borrow_data()?;
+            let (_, requested_legs) = BasketEscrow::legs(&data)?;
+
+            // Taker -> Maker, the protocol fee is charged on every requested leg
+            for (leg, accounts_leg) in requested_legs.iter().zip(requested.chunks_exact(4)) {
+                let fee = config.fee(leg.amount())?;
+
+                Transfer {
+                    from: &accounts_leg[1],
+                    to: &accounts_leg[2],
+                    authority: self.accounts.taker,
+                    amount: leg
+                        .amount()
+                        .checked_sub(fee)
+                        .ok_or(ProgramError::ArithmeticOverflow)?,
+                }
+                .invoke()?;
+
+                if fee.gt(&0) {
+                    Transfer {
+                        from: &accounts_leg[1],
+                        to: &accounts_leg[3],
+                        authority: self.accounts.taker,
+                        amount: fee,
+                    }
+                    .invoke()?;
+                }
+            }
+        }
+
+        ProgramAccount::close(self.accounts.basket, self.accounts.maker)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,376 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Escrow, Market},
+};
+
+use super::{
+    AccountCheck, AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount,
+    AssociatedTokenAccountCheck, ConfigAccount, MarketAccount, MintAccount, ProgramAccount,
+    SignerAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct TakeManyAccounts<'a> {
+    pub taker: &'a AccountInfo,
+    pub a_mint: &'a AccountInfo,
+    pub b_mint: &'a AccountInfo,
+    pub taker_ata_a: &'a AccountInfo,
+    pub taker_ata_b: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub fee_recipient: &'a AccountInfo,
+    pub fee_recipient_ata_b: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    // `[escrow, vault, maker, maker_ata_b]` for each offer, in fill order
+    pub offers: &'a [AccountInfo],
+    pub market: Option<&'a AccountInfo>, // trailing, required if any offer is indexed
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TakeManyAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [taker, a_mint, b_mint, taker_ata_a, taker_ata_b, config, fee_recipient, fee_recipient_ata_b, system_program, token_program, _associated_token_program, offers @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(taker)?;
+        MintAccount::check(a_mint)?;
+        MintAccount::check(b_mint)?;
+        AssociatedTokenAccount::check(taker_ata_b, b_mint, taker, token_program)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .fee_recipient
+            .ne(fee_recipient.key())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (offers, market) = MarketAccount::split_last(offers);
+
+        if offers.is_empty() || offers.len() % 4 != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            taker,
+            a_mint,
+            b_mint,
+            taker_ata_a,
+            taker_ata_b,
+            config,
+            fee_recipient,
+            fee_recipient_ata_b,
+            system_program,
+            token_program,
+            offers,
+            market,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct TakeManyInstructionData {
+    pub total: u64,       // exact `token_a` to receive, the last offer may be partial
+    pub limit_price: u64, // maximum `token_b` paid per `token_a`, scaled by `PRICE_SCALE`
+}
+
+impl<'a> TryFrom<&'a [u8]> for TakeManyInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let total = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let limit_price = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if total.eq(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { total, limit_price })
+    }
+}
+
+impl TakeManyInstructionData {
+    /// `limit_price` is in `token_b` base units per `token_a` base unit, times `PRICE_SCALE`.
+    pub const PRICE_SCALE: u128 = 1_000_000_000;
+
+    /// Whether paying `receive` for `amount` is within the taker's limit price.
+    #[inline(always)]
+    pub fn within_limit(&self, amount: u64, receive: u64) -> bool {
+        (receive as u128) * Self::PRICE_SCALE <= (amount as u128) * (self.limit_price as u128)
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct TakeMany<'a> {
+    pub accounts: TakeManyAccounts<'a>,
+    pub instruction_data: TakeManyInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for TakeMany<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = TakeManyInstructionData::try_from(data)?;
+        let accounts = TakeManyAccounts::try_from(accounts)?;
+
+        for offer in accounts.offers.chunks_exact(4) {
+            let [escrow, vault, maker, maker_ata_b] = offer else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            ProgramAccount::check(escrow)?;
+
+            {
+                let data = escrow.try_borrow_data()?;
+                let escrow_state = Escrow::load(&data)?;
+
+                let escrow_key = create_program_address(
+                    &[
+                        b"escrow",
+                        maker.key(),
+                        &escrow_state.seed().to_le_bytes(),
+                        &escrow_state.bump,
+                    ],
+                    &crate::ID,
+                )?;
+
+                // Oracle-priced offers need their price feed, take them one by one instead.
+                if escrow_key.ne(escrow.key())
+                    || escrow_state.token_a_mint.ne(accounts.a_mint.key())
+                    || escrow_state.token_b_mint.ne(accounts.b_mint.key())
+                    || escrow_state.is_oracle_priced()
+                {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                // Offers of the same pair may be swept whether they are indexed or not.
+                if escrow_state.is_indexed() {
+                    MarketAccount::check_escrow(accounts.market, escrow_state)?;
+                }
+            }
+
+            AssociatedTokenAccount::check(vault, accounts.a_mint, escrow, accounts.token_program)?;
+            AssociatedTokenAccount::init_if_needed(
+                accounts.taker,
+                maker_ata_b,
+                maker,
+                accounts.b_mint,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.taker_ata_a,
+            accounts.taker,
+            accounts.a_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker,
+            accounts.fee_recipient_ata_b,
+            accounts.fee_recipient,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> TakeMany<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let config_data = self.accounts.config.try_borrow_data()?;
+        let config = Config::load(&config_data)?;
+
+        if config.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut filled: u64 = 0;
+        let mut fees: u64 = 0;
+
+        // Offers are filled in order, skipping the ones priced above the limit. The last one is
+        // only filled for what is left of `total`, and stays open with the rest.
+        for offer in self.accounts.offers.chunks_exact(4) {
+            let left = self.instruction_data.total - filled;
+            if left.eq(&0) {
+                break;
+            }
+
+            let [escrow, vault, maker, maker_ata_b] = offer else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            let amount = pinocchio_token::state::TokenAccount::from_account_info(vault)?.amount();
+
+            let (seed, receive, min_fill, bump, indexed) = {
+                let data = escrow.try_borrow_data()?;
+                let escrow = Escrow::load(&data)?;
+
+                if escrow.is_hashlocked() {
+                    return Err(EscrowError::Hashlocked.into());
+                }
+
+                if escrow.is_expired(now) {
+                    return Err(EscrowError::OfferExpired.into());
+                }
+
+                if !escrow.can_be_taken_by(self.accounts.taker.key()) {
+                    return Err(EscrowError::InvalidTaker.into());
+                }
+
+                (
+                    escrow.seed(),
+                    escrow.receive(),
+                    escrow.min_fill(),
+                    escrow.bump,
+                    escrow.is_indexed(),
+                )
+            };
+
+            // A partial fill pays its share of `receive`, rounded up in the maker's favour.
+            let (fill, pay) = match amount.gt(&left) {
+                true => (left, Self::pro_rata(receive, left, amount)?),
+                false => (amount, receive),
+            };
+            let partial = fill.lt(&amount);
+
+            // Partial fills below the maker's minimum, or too small to leave anything to pay for
+            // the rest, are skipped like overpriced offers.
+            if !self.instruction_data.within_limit(fill, pay)
+                || (partial && (fill.lt(&min_fill) || pay.ge(&receive)))
+            {
+                continue;
+            }
+
+            let seed_binding = seed.to_le_bytes();
+            let seeds = [
+                Seed::from(b"escrow"),
+                Seed::from(maker.key().as_ref()),
+                Seed::from(&seed_binding),
+                Seed::from(&bump),
+            ];
+            let signers = [Signer::from(&seeds)];
+
+            // Vault -> Taker
+            Transfer {
+                from: vault,
+                to: self.accounts.taker_ata_a,
+                authority: escrow,
+                amount: fill,
+            }
+            .invoke_signed(&signers)?;
+
+            if !partial {
+                CloseAccount {
+                    account: vault,
+                    destination: maker,
+                    authority: escrow,
+                }
+                .invoke_signed(&signers)?;
+            }
+
+            // Taker -> Maker, net of the protocol fee
+            let fee = config.fee(pay)?;
+
+            Transfer {
+                from: self.accounts.taker_ata_b,
+                to: maker_ata_b,
+                authority: self.accounts.taker,
+                amount: pay
+                    .checked_sub(fee)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            }
+            .invoke()?;
+
+            match partial {
+                true => Escrow::load_mut(escrow.try_borrow_mut_data()?.as_mut())?
+                    .set_receive(receive - pay),
+                false => ProgramAccount::close(escrow, maker)?,
+            }
+
+            if let (true, Some(market)) = (indexed, self.accounts.market) {
+                let mut data = market.try_borrow_mut_data()?;
+                let market = Market::load_mut(data.as_mut())?;
+
+                match partial {
+                    true => market.record_partial_fill(fill, pay)?,
+                    false => market.record_fill(fill, pay)?,
+                }
+            }
+
+            filled += fill;
+            fees = fees
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        if filled < self.instruction_data.total {
+            return Err(EscrowError::TotalNotReached.into());
+        }
+
+        // Taker -> Fee recipient, once for the whole sweep
+        if fees.gt(&0) {
+            Transfer {
+                from: self.accounts.taker_ata_b,
+                to: self.accounts.fee_recipient_ata_b,
+                authority: self.accounts.taker,
+                amount: fees,
+            }
+            .invoke()?;
+        }
+
+        Ok(())
+    }
+
+    /// `receive * fill / amount`, rounded up.
+    fn pro_rata(receive: u64, fill: u64, amount: u64) -> Result<u64, ProgramError> {
+        let pay = (receive as u128 * fill as u128).div_ceil(amount as u128);
+
+        u64::try_from(pay).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
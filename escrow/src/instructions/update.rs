@@ -0,0 +1,187 @@
+use accounts_derive::Accounts;
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use pinocchio_token::instructions::Transfer;
+
+use crate::{
+    errors::EscrowError,
+    loadable::Loadable,
+    state::{Config, Escrow},
+};
+
+use super::{ConfigAccount, ProgramAccount};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct UpdateAccounts<'a> {
+    #[signer]
+    pub maker: &'a AccountInfo,
+    #[check(ProgramAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub a_mint: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = maker)]
+    pub maker_ata_a: &'a AccountInfo,
+    #[ata(mint = a_mint, authority = escrow)]
+    pub escrow_ata: &'a AccountInfo, // vault
+    #[check(ConfigAccount)]
+    pub config: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct UpdateInstructionData {
+    pub receive: u64,
+    pub deposit: u64,  // Maker -> Vault
+    pub withdraw: u64, // Vault -> Maker
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 3 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let receive = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let deposit = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let withdraw = u64::from_le_bytes(data[16..24].try_into().unwrap());
+
+        if deposit.ne(&0) && withdraw.ne(&0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            receive,
+            deposit,
+            withdraw,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct Update<'a> {
+    pub accounts: UpdateAccounts<'a>,
+    pub instruction_data: UpdateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Update<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = UpdateInstructionData::try_from(data)?;
+        let accounts = UpdateAccounts::try_from(accounts)?;
+
+        // Amending an offer is as good as making a new one, makers can still `Refund`.
+        if Config::load(&accounts.config.try_borrow_data()?)?.is_paused() {
+            return Err(EscrowError::ProgramPaused.into());
+        }
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = Escrow::load(&data)?;
+
+            // The escrow address is derived from the maker, so this also checks that the signer
+            // is the escrow's maker.
+            let escrow_key = create_program_address(
+                &[
+                    b"escrow",
+                    accounts.maker.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.token_a_mint.ne(accounts.a_mint.key())
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.is_hashlocked() {
+                return Err(EscrowError::Hashlocked.into());
+            }
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Update<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump) = {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+            let escrow = Escrow::load_mut(data.as_mut())?;
+
+            escrow.set_receive(self.instruction_data.receive);
+
+            (escrow.seed(), escrow.bump)
+        };
+
+        // Maker -> Vault
+        if self.instruction_data.deposit.gt(&0) {
+            Transfer {
+                from: self.accounts.maker_ata_a,
+                to: self.accounts.escrow_ata,
+                authority: self.accounts.maker,
+                amount: self.instruction_data.deposit,
+            }
+            .invoke()?;
+        }
+
+        // Vault -> Maker
+        if self.instruction_data.withdraw.gt(&0) {
+            let amount =
+                pinocchio_token::state::TokenAccount::from_account_info(self.accounts.escrow_ata)?
+                    .amount();
+
+            // Emptying the vault is a `Refund`, the escrow must keep something to offer.
+            if self.instruction_data.withdraw >= amount {
+                return Err(ProgramError::InsufficientFunds);
+            }
+
+            let seed_binding = seed.to_le_bytes();
+            let seeds = [
+                Seed::from(b"escrow"),
+                Seed::from(self.accounts.maker.key().as_ref()),
+                Seed::from(&seed_binding),
+                Seed::from(&bump),
+            ];
+            let signers = [Signer::from(&seeds)];
+
+            Transfer {
+                from: self.accounts.escrow_ata,
+                to: self.accounts.maker_ata_a,
+                authority: self.accounts.escrow,
+                amount: self.instruction_data.withdraw,
+            }
+            .invoke_signed(&signers)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use core::mem::size_of;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::Config};
+
+use super::{AccountCheck, ConfigAccount, SignerAccount};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+pub struct UpdateConfigAccounts<'a> {
+    pub admin: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(admin)?;
+        ConfigAccount::check(config)?;
+
+        if Config::load(&config.try_borrow_data()?)?
+            .admin
+            .ne(admin.key())
+        {
+            return Err(EscrowError::Unauthorized.into());
+        }
+
+        Ok(Self { admin, config })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction Data Context
+ * ==========================
+ */
+pub struct UpdateConfigInstructionData {
+    pub fee_bps: u16,
+    pub referral_share_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub admin: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() * 2 + size_of::<Pubkey>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_bps = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let referral_share_bps = u16::from_le_bytes(data[2..4].try_into().unwrap());
+        let fee_recipient: Pubkey = data[4..36].try_into().unwrap();
+        let admin: Pubkey = data[36..68].try_into().unwrap();
+
+        if fee_bps > Config::MAX_FEE_BPS || referral_share_bps > Config::MAX_FEE_BPS {
+            return Err(EscrowError::InvalidFeeBps.into());
+        }
+
+        Ok(Self {
+            fee_bps,
+            referral_share_bps,
+            fee_recipient,
+            admin,
+        })
+    }
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct UpdateConfig<'a> {
+    pub accounts: UpdateConfigAccounts<'a>,
+    pub instruction_data: UpdateConfigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let instruction_data = UpdateConfigInstructionData::try_from(data)?;
+        let accounts = UpdateConfigAccounts::try_from(accounts)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+        let config = Config::load_mut(data.as_mut())?;
+
+        config.set_fee_bps(self.instruction_data.fee_bps);
+        config.set_referral_share_bps(self.instruction_data.referral_share_bps);
+        config.set_fee_recipient(self.instruction_data.fee_recipient);
+        config.set_admin(self.instruction_data.admin);
+
+        Ok(())
+    }
+}
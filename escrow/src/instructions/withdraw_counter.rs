@@ -0,0 +1,129 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{loadable::Loadable, state::CounterOffer};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, CounterOfferAccount,
+    ProgramAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct WithdrawCounterAccounts<'a> {
+    #[signer]
+    pub proposer: &'a AccountInfo,
+    pub escrow: &'a AccountInfo, // may already be closed
+    #[check(CounterOfferAccount)]
+    pub counter: &'a AccountInfo,
+    #[mint]
+    pub b_mint: &'a AccountInfo,
+    #[ata(mint = b_mint, authority = counter)]
+    pub counter_ata_b: &'a AccountInfo,
+    pub proposer_ata_b: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct WithdrawCounter<'a> {
+    pub accounts: WithdrawCounterAccounts<'a>,
+    pub bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawCounter<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = WithdrawCounterAccounts::try_from(accounts)?;
+
+        let bump = {
+            let data = accounts.counter.try_borrow_data()?;
+            let counter = CounterOffer::load(&data)?;
+
+            // The counter offer address is derived from the proposer, so this also checks that
+            // the signer is its proposer.
+            let counter_key = create_program_address(
+                &[
+                    b"counter",
+                    accounts.escrow.key(),
+                    accounts.proposer.key(),
+                    &counter.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if counter_key.ne(accounts.counter.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            counter.bump
+        };
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.proposer,
+            accounts.proposer_ata_b,
+            accounts.proposer,
+            accounts.b_mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts, bump })
+    }
+}
+
+impl<'a> WithdrawCounter<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    // Not gated by `Config::paused`, and works after the escrow itself was settled or refunded.
+    pub fn process(&mut self) -> ProgramResult {
+        let seeds = [
+            Seed::from(b"counter"),
+            Seed::from(self.accounts.escrow.key().as_ref()),
+            Seed::from(self.accounts.proposer.key().as_ref()),
+            Seed::from(&self.bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        let amount =
+            pinocchio_token::state::TokenAccount::from_account_info(self.accounts.counter_ata_b)?
+                .amount();
+
+        // Counter offer -> Proposer
+        Transfer {
+            from: self.accounts.counter_ata_b,
+            to: self.accounts.proposer_ata_b,
+            authority: self.accounts.counter,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        CloseAccount {
+            account: self.accounts.counter_ata_b,
+            destination: self.accounts.proposer,
+            authority: self.accounts.counter,
+        }
+        .invoke_signed(&signers)?;
+
+        ProgramAccount::close(self.accounts.counter, self.accounts.proposer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use accounts_derive::Accounts;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_token::instructions::{CloseAccount, Transfer};
+
+use crate::{errors::EscrowError, loadable::Loadable, state::StreamEscrow};
+
+use super::{
+    AccountClose, AssociateTokenAccountInit, AssociatedTokenAccount, ProgramAccount, StreamAccount,
+};
+
+/*
+ * =============================
+ * Accounts Context
+ * =============================
+ */
+#[derive(Accounts)]
+pub struct WithdrawStreamAccounts<'a> {
+    #[signer]
+    pub recipient: &'a AccountInfo,
+    pub sender: &'a AccountInfo, // gets the rent back once the stream is depleted
+    #[check(StreamAccount)]
+    pub escrow: &'a AccountInfo,
+    #[mint]
+    pub mint: &'a AccountInfo,
+    #[ata(mint = mint, authority = escrow)]
+    pub vault: &'a AccountInfo,
+    pub recipient_ata: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+/*
+ * ==========================
+ * Instruction
+ * ==========================
+ */
+pub struct WithdrawStream<'a> {
+    pub accounts: WithdrawStreamAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawStream<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = WithdrawStreamAccounts::try_from(accounts)?;
+
+        {
+            let data = accounts.escrow.try_borrow_data()?;
+            let escrow = StreamEscrow::load(&data)?;
+
+            let escrow_key = create_program_address(
+                &[
+                    b"stream",
+                    accounts.sender.key(),
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &crate::ID,
+            )?;
+
+            if escrow_key.ne(accounts.escrow.key()) || escrow.mint.ne(accounts.mint.key()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            if escrow.recipient.ne(accounts.recipient.key()) {
+                return Err(EscrowError::Unauthorized.into());
+            }
+        }
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.recipient,
+            accounts.recipient_ata,
+            accounts.recipient,
+            accounts.mint,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> WithdrawStream<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    // Not gated by `Config::paused`, accrued tokens belong to the recipient.
+    pub fn process(&mut self) -> ProgramResult {
+        let (seed, bump, amount, is_depleted) = {
+            let mut data = self.accounts.escrow.try_borrow_mut_data()?;
+            let escrow = StreamEscrow::load_mut(data.as_mut())?;
+
+            let amount = escrow.withdrawable(Clock::get()?.unix_timestamp);
+
+            if amount.eq(&0) {
+                return Err(EscrowError::NothingToClaim.into());
+            }
+
+            escrow.set_withdrawn(escrow.withdrawn() + amount);
+
+            (escrow.seed(), escrow.bump, amount, escrow.is_depleted())
+        };
+
+        // The last withdrawal empties the vault, so that anything sent to it on top of the
+        // deposit doesn't keep it from closing.
+        let amount = match is_depleted {
+            true => pinocchio_token::state::TokenAccount::from_account_info(self.accounts.vault)?
+                .amount(),
+            false => amount,
+        };
+
+        let seed_binding = seed.to_le_bytes();
+        let seeds = [
+            Seed::from(b"stream"),
+            Seed::from(self.accounts.sender.key().as_ref()),
+            Seed::from(&seed_binding),
+            Seed::from(&bump),
+        ];
+        let signers = [Signer::from(&seeds)];
+
+        // Vault -> Recipient
+        Transfer {
+            from: self.accounts.vault,
+            to: self.accounts.recipient_ata,
+            authority: self.accounts.escrow,
+            amount,
+        }
+        .invoke_signed(&signers)?;
+
+        if is_depleted {
+            CloseAccount {
+                account: self.accounts.vault,
+                destination: self.accounts.sender,
+                authority: self.accounts.escrow,
+            }
+            .invoke_signed(&signers)?;
+
+            ProgramAccount::close(self.accounts.escrow, self.accounts.sender)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
 #![no_std]
 
-use instructions::Make;
+use instructions::{
+    AcceptCounter, CancelStream, Claim, CloseExpired, Dispute, FillOrder, InitConfig, InitMarket,
+    Make, MakeBasket, MakeMilestone, MakeStream, MakeVesting, Migrate, ProposeCounter,
+    ReclaimMilestones, Redeem, Refund, RefundBasket, RegisterReferral, ReleaseMilestone, Resolve,
+    SetPaused, Take, TakeBasket, TakeMany, Update, UpdateConfig, WithdrawCounter, WithdrawStream,
+};
 use pinocchio::{
     account_info::AccountInfo, entrypoint, nostd_panic_handler, program_error::ProgramError,
     pubkey::Pubkey, ProgramResult,
@@ -9,7 +14,9 @@ use pinocchio::{
 entrypoint!(process_instruction);
 nostd_panic_handler!();
 
+pub mod errors;
 pub mod instructions;
+pub mod loadable;
 pub mod state;
 pub mod utils;
 
@@ -27,6 +34,55 @@ fn process_instruction(
 ) -> ProgramResult {
     match instruction_data.split_first() {
         Some((Make::DISCRIMINATOR, data)) => Make::try_from((data, accounts))?.process(),
+        Some((Take::DISCRIMINATOR, data)) => Take::try_from((data, accounts))?.process(),
+        Some((CloseExpired::DISCRIMINATOR, _)) => CloseExpired::try_from(accounts)?.process(),
+        Some((InitConfig::DISCRIMINATOR, data)) => {
+            InitConfig::try_from((data, accounts))?.process()
+        }
+        Some((UpdateConfig::DISCRIMINATOR, data)) => {
+            UpdateConfig::try_from((data, accounts))?.process()
+        }
+        Some((Refund::DISCRIMINATOR, _)) => Refund::try_from(accounts)?.process(),
+        Some((SetPaused::DISCRIMINATOR, data)) => SetPaused::try_from((data, accounts))?.process(),
+        Some((Update::DISCRIMINATOR, data)) => Update::try_from((data, accounts))?.process(),
+        Some((MakeBasket::DISCRIMINATOR, data)) => {
+            MakeBasket::try_from((data, accounts))?.process()
+        }
+        Some((TakeBasket::DISCRIMINATOR, _)) => TakeBasket::try_from(accounts)?.process(),
+        Some((RefundBasket::DISCRIMINATOR, _)) => RefundBasket::try_from(accounts)?.process(),
+        Some((FillOrder::DISCRIMINATOR, data)) => FillOrder::try_from((data, accounts))?.process(),
+        Some((TakeMany::DISCRIMINATOR, data)) => TakeMany::try_from((data, accounts))?.process(),
+        Some((ProposeCounter::DISCRIMINATOR, data)) => {
+            ProposeCounter::try_from((data, accounts))?.process()
+        }
+        Some((AcceptCounter::DISCRIMINATOR, _)) => AcceptCounter::try_from(accounts)?.process(),
+        Some((WithdrawCounter::DISCRIMINATOR, _)) => WithdrawCounter::try_from(accounts)?.process(),
+        Some((MakeMilestone::DISCRIMINATOR, data)) => {
+            MakeMilestone::try_from((data, accounts))?.process()
+        }
+        Some((ReleaseMilestone::DISCRIMINATOR, _)) => {
+            ReleaseMilestone::try_from(accounts)?.process()
+        }
+        Some((ReclaimMilestones::DISCRIMINATOR, _)) => {
+            ReclaimMilestones::try_from(accounts)?.process()
+        }
+        Some((Dispute::DISCRIMINATOR, _)) => Dispute::try_from(accounts)?.process(),
+        Some((Resolve::DISCRIMINATOR, data)) => Resolve::try_from((data, accounts))?.process(),
+        Some((MakeVesting::DISCRIMINATOR, data)) => {
+            MakeVesting::try_from((data, accounts))?.process()
+        }
+        Some((Claim::DISCRIMINATOR, _)) => Claim::try_from(accounts)?.process(),
+        Some((MakeStream::DISCRIMINATOR, data)) => {
+            MakeStream::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawStream::DISCRIMINATOR, _)) => WithdrawStream::try_from(accounts)?.process(),
+        Some((CancelStream::DISCRIMINATOR, _)) => CancelStream::try_from(accounts)?.process(),
+        Some((Redeem::DISCRIMINATOR, data)) => Redeem::try_from((data, accounts))?.process(),
+        Some((InitMarket::DISCRIMINATOR, _)) => InitMarket::try_from(accounts)?.process(),
+        Some((Migrate::DISCRIMINATOR, _)) => Migrate::try_from(accounts)?.process(),
+        Some((RegisterReferral::DISCRIMINATOR, _)) => {
+            RegisterReferral::try_from(accounts)?.process()
+        }
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -0,0 +1,55 @@
+use pinocchio::program_error::ProgramError;
+
+/// Zero-copy view of a program account's data.
+///
+/// Account data is only guaranteed to be 8-byte aligned at the start of the account, so views
+/// taken at an offset into it, or into a buffer coming from anywhere else, may be misaligned.
+/// Implementors therefore have an alignment of `1`: they are `#[repr(C)]` and made only of
+/// byte arrays and `u8`s, with wider integers stored little-endian behind accessors. A reference
+/// to one can be taken at any address.
+///
+/// # Safety
+///
+/// Implementors must have an alignment of `1`, a size of `LEN`, and no invalid bit patterns.
+/// `assert_loadable!` checks the first two at compile time.
+pub unsafe trait Loadable: Sized {
+    /// Size of the view in the account data.
+    const LEN: usize;
+
+    /// Checks that `bytes` hold a `Self` and returns it.
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError>;
+
+    /// Checks that `bytes` hold a `Self` and returns it for writing.
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError>;
+
+    /// Returns the first `LEN` bytes of `bytes` as a `Self`, without any check of their content.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be at least `LEN` bytes long.
+    #[inline(always)]
+    unsafe fn load_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Self)
+    }
+
+    /// Returns the first `LEN` bytes of `bytes` as a `Self` for writing, without any check of
+    /// their content.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be at least `LEN` bytes long.
+    #[inline(always)]
+    unsafe fn load_mut_unchecked(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Self)
+    }
+}
+
+/// Fails to compile unless `$type` can implement `Loadable`.
+#[macro_export]
+macro_rules! assert_loadable {
+    ($type:ty) => {
+        const _: () = assert!(core::mem::align_of::<$type>() == 1);
+        const _: () =
+            assert!(core::mem::size_of::<$type>() == <$type as $crate::loadable::Loadable>::LEN);
+    };
+}
@@ -1,40 +1,241 @@
 use core::mem::size_of;
 use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
 
+use crate::{assert_loadable, loadable::Loadable};
+
+/// Prefix of every program account. The discriminator tells account types apart, so that an
+/// account can't be passed off as another one of the same size, and the version tells layouts
+/// apart, so that fields can be added without breaking the accounts created before them.
+#[repr(C)]
+pub struct AccountHeader {
+    pub discriminator: u8,
+    pub version: u8,
+    _padding: [u8; 6], // keeps the body 8-byte aligned
+}
+
+assert_loadable!(AccountHeader);
+
+unsafe impl Loadable for AccountHeader {
+    const LEN: usize = size_of::<u8>() + // discriminator
+        size_of::<u8>() +       // version
+        size_of::<[u8; 6]>(); // padding
+
+    /// Only checks that the header fits, the account data goes on after it.
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        if bytes.len() < AccountHeader::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { AccountHeader::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if bytes.len() < AccountHeader::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { AccountHeader::load_mut_unchecked(bytes) })
+    }
+}
+
+impl AccountHeader {
+    /// Version of accounts whose layout hasn't changed since the header was introduced.
+    pub const VERSION: u8 = 1;
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, discriminator: u8, version: u8) {
+        self.discriminator = discriminator;
+        self.version = version;
+        self._padding = [0; 6];
+    }
+
+    #[inline(always)]
+    pub fn check(&self, discriminator: u8) -> Result<(), ProgramError> {
+        if self.discriminator.ne(&discriminator) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the data after the header of an account of the given type.
+    #[inline(always)]
+    pub fn body(bytes: &[u8], discriminator: u8) -> Result<&[u8], ProgramError> {
+        AccountHeader::load(bytes)?.check(discriminator)?;
+
+        Ok(&bytes[AccountHeader::LEN..])
+    }
+
+    #[inline(always)]
+    pub fn body_mut(bytes: &mut [u8], discriminator: u8) -> Result<&mut [u8], ProgramError> {
+        AccountHeader::load(bytes)?.check(discriminator)?;
+
+        Ok(&mut bytes[AccountHeader::LEN..])
+    }
+
+    /// Writes the header of a freshly allocated account of the given type.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8], discriminator: u8, version: u8) -> Result<(), ProgramError> {
+        let header = AccountHeader::load_mut(bytes)?;
+
+        if header.discriminator.ne(&0) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        header.set_inner(discriminator, version);
+
+        Ok(())
+    }
+}
+
+// Integers are stored as little-endian byte arrays, see `Loadable`.
 #[repr(C)]
 pub struct Escrow {
     pub maker: Pubkey,
     pub token_a_mint: Pubkey,
     pub token_b_mint: Pubkey,
-    pub receive: u64,
-    pub seed: u64,
+    pub taker: Pubkey,
+    pub price_feed: Pubkey,
+    pub hashlock: [u8; 32],
+    receive: [u8; 8],
+    seed: [u8; 8],
+    expiry: [u8; 8],
+    min_fill: [u8; 8], // smallest partial fill the maker accepts, `0` means any
+    spread_bps: [u8; 2],
     pub bump: [u8; 1],
+    indexed: u8, // counted in its pair's `Market`
 }
 
-impl Escrow {
-    pub const LEN: usize = size_of::<Pubkey>() + // Maker
+assert_loadable!(Escrow);
+
+unsafe impl Loadable for Escrow {
+    const LEN: usize = size_of::<Pubkey>() + // Maker
         size_of::<Pubkey>() +   // token_a_mint
         size_of::<Pubkey>() +   // token_b_mint
+        size_of::<Pubkey>() +   // taker
+        size_of::<Pubkey>() +   // price_feed
+        size_of::<[u8;32]>() +  // hashlock
         size_of::<u64>() +      // receive
         size_of::<u64>() +      // seed
-        size_of::<[u8;1]>(); // bump
+        size_of::<i64>() +      // expiry
+        size_of::<u64>() +      // min_fill
+        size_of::<u16>() +      // spread_bps
+        size_of::<[u8;1]>() +   // bump
+        size_of::<u8>(); // indexed
 
+    /// Only accepts current escrow accounts, older ones have to be migrated first.
     #[inline(always)]
-    pub fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
-        if bytes.len() != Escrow::LEN {
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        if Escrow::version(bytes)?.ne(&Escrow::VERSION) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(unsafe { &*core::mem::transmute::<*const u8, *const Self>(bytes.as_ptr()) })
+        Ok(unsafe { Escrow::load_unchecked(&bytes[AccountHeader::LEN..]) })
     }
 
     #[inline(always)]
-    pub fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
-        if bytes.len() != Escrow::LEN {
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if Escrow::version(bytes)?.ne(&Escrow::VERSION) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Escrow::load_mut_unchecked(&mut bytes[AccountHeader::LEN..]) })
+    }
+}
+
+impl Escrow {
+    pub const DISCRIMINATOR: u8 = 1;
+
+    /// Version `0` escrows predate the header, see `EscrowV0`.
+    pub const VERSION: u8 = 1;
+
+    /// Space allocated for a current escrow account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + Escrow::LEN;
+
+    /// The layout version of the escrow account in `bytes`.
+    pub fn version(bytes: &[u8]) -> Result<u8, ProgramError> {
+        match bytes.len() {
+            EscrowV0::LEN => Ok(0),
+            Escrow::SPACE => {
+                let header = AccountHeader::load(bytes)?;
+                header.check(Escrow::DISCRIMINATOR)?;
+
+                if header.version.eq(&0) || header.version.gt(&Escrow::VERSION) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                Ok(header.version)
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Writes the current header into a freshly allocated escrow account and returns its body.
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if bytes.len() != Escrow::SPACE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        AccountHeader::init(bytes, Escrow::DISCRIMINATOR, Escrow::VERSION)?;
+
+        Escrow::load_mut(bytes)
+    }
+
+    /// Rewrites an older escrow account, already resized to `SPACE`, in the current layout.
+    /// Fields it didn't have get their defaults: public, fixed-price, never expiring, with no
+    /// hashlock, no minimum fill and not indexed.
+    pub fn migrate(bytes: &mut [u8], version: u8) -> Result<(), ProgramError> {
+        if bytes.len() != Escrow::SPACE || version.ne(&0) {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        Ok(unsafe { &mut *core::mem::transmute::<*mut u8, *mut Self>(bytes.as_mut_ptr()) })
+        let (maker, token_a_mint, token_b_mint, seed, receive, bump) = {
+            let legacy = EscrowV0::load(&bytes[..EscrowV0::LEN])?;
+
+            (
+                legacy.maker,
+                legacy.token_a_mint,
+                legacy.token_b_mint,
+                legacy.seed(),
+                legacy.receive(),
+                legacy.bump,
+            )
+        };
+
+        bytes.fill(0);
+
+        Escrow::init(bytes)?.set_inner(maker, token_a_mint, token_b_mint, seed, receive, bump);
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    /// Smallest partial fill the maker accepts, checked by `ProposeCounter` and `TakeMany`.
+    /// `Take` always fills the whole vault, so it is never below it.
+    #[inline(always)]
+    pub fn min_fill(&self) -> u64 {
+        u64::from_le_bytes(self.min_fill)
+    }
+
+    #[inline(always)]
+    pub fn spread_bps(&self) -> u16 {
+        u16::from_le_bytes(self.spread_bps)
     }
 
     #[inline(always)]
@@ -52,14 +253,24 @@ impl Escrow {
         self.token_b_mint = token_b_mint;
     }
 
+    #[inline(always)]
+    pub fn set_taker(&mut self, taker: Pubkey) {
+        self.taker = taker;
+    }
+
     #[inline(always)]
     pub fn set_seed(&mut self, seed: u64) {
-        self.seed = seed;
+        self.seed = seed.to_le_bytes();
     }
 
     #[inline(always)]
     pub fn set_receive(&mut self, receive: u64) {
-        self.receive = receive;
+        self.receive = receive.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_expiry(&mut self, expiry: i64) {
+        self.expiry = expiry.to_le_bytes();
     }
 
     #[inline(always)]
@@ -67,6 +278,27 @@ impl Escrow {
         self.bump = bump;
     }
 
+    #[inline(always)]
+    pub fn set_oracle(&mut self, price_feed: Pubkey, spread_bps: u16) {
+        self.price_feed = price_feed;
+        self.spread_bps = spread_bps.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_hashlock(&mut self, hashlock: [u8; 32]) {
+        self.hashlock = hashlock;
+    }
+
+    #[inline(always)]
+    pub fn set_indexed(&mut self, indexed: bool) {
+        self.indexed = indexed as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_min_fill(&mut self, min_fill: u64) {
+        self.min_fill = min_fill.to_le_bytes();
+    }
+
     pub fn set_inner(
         &mut self,
         maker: Pubkey,
@@ -79,8 +311,1334 @@ impl Escrow {
         self.maker = maker;
         self.token_a_mint = token_a_mint;
         self.token_b_mint = token_b_mint;
-        self.receive = receive;
-        self.seed = seed;
+        self.receive = receive.to_le_bytes();
+        self.seed = seed.to_le_bytes();
+        self.bump = bump;
+    }
+
+    /// An `expiry` of `0` means the offer never expires.
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry().ne(&0) && now > self.expiry()
+    }
+
+    /// A zeroed `taker` means the offer is public and anyone can fill it.
+    #[inline(always)]
+    pub fn can_be_taken_by(&self, taker: &Pubkey) -> bool {
+        self.taker.eq(&Pubkey::default()) || self.taker.eq(taker)
+    }
+
+    /// A hashlocked escrow can only be redeemed by revealing the SHA-256 preimage of
+    /// `hashlock` before `expiry`, or refunded after it. A zeroed `hashlock` means none.
+    #[inline(always)]
+    pub fn is_hashlocked(&self) -> bool {
+        self.hashlock.ne(&[0; 32])
+    }
+
+    /// An indexed escrow must be settled or refunded along with its pair's `Market`.
+    #[inline(always)]
+    pub fn is_indexed(&self) -> bool {
+        self.indexed.ne(&0)
+    }
+
+    /// A zeroed `price_feed` means the offer is priced at a fixed `receive`.
+    #[inline(always)]
+    pub fn is_oracle_priced(&self) -> bool {
+        self.price_feed.ne(&Pubkey::default())
+    }
+
+    /// The `token_b` owed for `amount` of `token_a` at an oracle price of `price * 10^exponent`
+    /// `token_b` per `token_a`, marked up by `spread_bps` and rounded up. `receive` acts as a
+    /// floor.
+    pub fn quote(
+        &self,
+        amount: u64,
+        price: u64,
+        exponent: i32,
+        a_decimals: u8,
+        b_decimals: u8,
+    ) -> Result<u64, ProgramError> {
+        let value = (amount as u128)
+            .checked_mul(price as u128)
+            .and_then(|value| value.checked_mul(10_000 + self.spread_bps() as u128))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // Scale from `token_a` base units to `token_b` base units, net of the bps denominator.
+        let scale = exponent + b_decimals as i32 - a_decimals as i32 - 4;
+        let factor = 10u128
+            .checked_pow(scale.unsigned_abs())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let value = if scale >= 0 {
+            value
+                .checked_mul(factor)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+        } else {
+            value.div_ceil(factor)
+        };
+
+        u64::try_from(value)
+            .map(|value| value.max(self.receive()))
+            .map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// Layout of the escrows created before the account header and the optional terms, only read
+/// to migrate them. Integers are stored little-endian, as they were.
+#[repr(C)]
+pub struct EscrowV0 {
+    pub maker: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    receive: [u8; 8],
+    seed: [u8; 8],
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(EscrowV0);
+
+unsafe impl Loadable for EscrowV0 {
+    const LEN: usize = size_of::<Pubkey>() + // Maker
+        size_of::<Pubkey>() +   // token_a_mint
+        size_of::<Pubkey>() +   // token_b_mint
+        size_of::<u64>() +      // receive
+        size_of::<u64>() +      // seed
+        size_of::<[u8;1]>(); // bump
+
+    /// Legacy escrows have no header to check, callers must also check that the account
+    /// address derives from the maker, seed and bump it holds.
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        if bytes.len() != EscrowV0::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { EscrowV0::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if bytes.len() != EscrowV0::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { EscrowV0::load_mut_unchecked(bytes) })
+    }
+}
+
+impl EscrowV0 {
+    #[inline(always)]
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+}
+
+#[repr(C)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    fee_bps: [u8; 2],
+    referral_share_bps: [u8; 2],
+    pub paused: u8,
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(Config);
+
+unsafe impl Loadable for Config {
+    const LEN: usize = size_of::<Pubkey>() + // admin
+        size_of::<Pubkey>() +   // fee_recipient
+        size_of::<u16>() +      // fee_bps
+        size_of::<u16>() +      // referral_share_bps
+        size_of::<u8>() +       // paused
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, Config::DISCRIMINATOR)?;
+
+        if bytes.len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Config::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, Config::DISCRIMINATOR)?;
+
+        if bytes.len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Config::load_mut_unchecked(bytes) })
+    }
+}
+
+impl Config {
+    pub const DISCRIMINATOR: u8 = 2;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + Config::LEN;
+
+    pub const MAX_FEE_BPS: u16 = 10_000;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, Config::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        Config::load_mut(bytes)
+    }
+
+    #[inline(always)]
+    pub fn fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn referral_share_bps(&self) -> u16 {
+        u16::from_le_bytes(self.referral_share_bps)
+    }
+
+    #[inline(always)]
+    pub fn set_admin(&mut self, admin: Pubkey) {
+        self.admin = admin;
+    }
+
+    #[inline(always)]
+    pub fn set_fee_recipient(&mut self, fee_recipient: Pubkey) {
+        self.fee_recipient = fee_recipient;
+    }
+
+    #[inline(always)]
+    pub fn set_fee_bps(&mut self, fee_bps: u16) {
+        self.fee_bps = fee_bps.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_referral_share_bps(&mut self, referral_share_bps: u16) {
+        self.referral_share_bps = referral_share_bps.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8; 1]) {
         self.bump = bump;
     }
+
+    pub fn set_inner(
+        &mut self,
+        admin: Pubkey,
+        fee_recipient: Pubkey,
+        fee_bps: u16,
+        referral_share_bps: u16,
+        bump: [u8; 1],
+    ) {
+        self.admin = admin;
+        self.fee_recipient = fee_recipient;
+        self.fee_bps = fee_bps.to_le_bytes();
+        self.referral_share_bps = referral_share_bps.to_le_bytes();
+        self.paused = 0;
+        self.bump = bump;
+    }
+
+    /// While paused, no new escrows can be made or filled. Refunds are always allowed.
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.paused.ne(&0)
+    }
+
+    /// Protocol fee owed on `amount`, rounded down.
+    #[inline(always)]
+    pub fn fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        (amount as u128)
+            .checked_mul(self.fee_bps() as u128)
+            .and_then(|fee| fee.checked_div(Config::MAX_FEE_BPS as u128))
+            .and_then(|fee| u64::try_from(fee).ok())
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Splits `fee` into `(protocol, referral)` parts, the referrer's share is rounded down.
+    #[inline(always)]
+    pub fn split_fee(&self, fee: u64) -> Result<(u64, u64), ProgramError> {
+        let referral = (fee as u128)
+            .checked_mul(self.referral_share_bps() as u128)
+            .and_then(|share| share.checked_div(Config::MAX_FEE_BPS as u128))
+            .and_then(|share| u64::try_from(share).ok())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        Ok((fee - referral, referral))
+    }
+}
+
+#[repr(C)]
+pub struct Referral {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    volume: [u8; 8],
+    fees: [u8; 8],
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(Referral);
+
+unsafe impl Loadable for Referral {
+    const LEN: usize = size_of::<Pubkey>() + // referrer
+        size_of::<Pubkey>() +   // mint
+        size_of::<u64>() +      // volume
+        size_of::<u64>() +      // fees
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, Referral::DISCRIMINATOR)?;
+
+        if bytes.len() != Referral::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Referral::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, Referral::DISCRIMINATOR)?;
+
+        if bytes.len() != Referral::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Referral::load_mut_unchecked(bytes) })
+    }
+}
+
+impl Referral {
+    pub const DISCRIMINATOR: u8 = 3;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + Referral::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, Referral::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        Referral::load_mut(bytes)
+    }
+
+    pub fn set_inner(&mut self, referrer: Pubkey, mint: Pubkey, bump: [u8; 1]) {
+        self.referrer = referrer;
+        self.mint = mint;
+        self.volume = [0; 8];
+        self.fees = [0; 8];
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn volume(&self) -> u64 {
+        u64::from_le_bytes(self.volume)
+    }
+
+    #[inline(always)]
+    pub fn fees(&self) -> u64 {
+        u64::from_le_bytes(self.fees)
+    }
+
+    /// Adds a routed fill of `volume` (in `mint`) that paid the referrer `fees`.
+    #[inline(always)]
+    pub fn record(&mut self, volume: u64, fees: u64) -> Result<(), ProgramError> {
+        self.volume = self
+            .volume()
+            .checked_add(volume)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        self.fees = self
+            .fees()
+            .checked_add(fees)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+}
+
+/// A single mint/amount pair of a basket trade.
+#[repr(C)]
+pub struct Leg {
+    pub mint: Pubkey,
+    amount: [u8; 8],
+}
+
+assert_loadable!(Leg);
+
+unsafe impl Loadable for Leg {
+    const LEN: usize = size_of::<Pubkey>() + // mint
+        size_of::<u64>(); // amount
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        if bytes.len() != Leg::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Leg::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if bytes.len() != Leg::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Leg::load_mut_unchecked(bytes) })
+    }
+}
+
+impl Leg {
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    #[inline(always)]
+    pub fn set_amount(&mut self, amount: u64) {
+        self.amount = amount.to_le_bytes();
+    }
+}
+
+/// Header of a basket escrow, followed in the account data by `offer_count` offered legs
+/// (each locked in its own escrow-owned ATA) and then `request_count` requested legs.
+#[repr(C)]
+pub struct BasketEscrow {
+    pub maker: Pubkey,
+    seed: [u8; 8],
+    expiry: [u8; 8],
+    pub offer_count: u8,
+    pub request_count: u8,
+    pub bump: [u8; 1],
+    _padding: [u8; 5],
+}
+
+assert_loadable!(BasketEscrow);
+
+unsafe impl Loadable for BasketEscrow {
+    const LEN: usize = size_of::<Pubkey>() + // maker
+        size_of::<u64>() +      // seed
+        size_of::<i64>() +      // expiry
+        size_of::<u8>() +       // offer_count
+        size_of::<u8>() +       // request_count
+        size_of::<[u8;1]>() +   // bump
+        size_of::<[u8;5]>(); // padding
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let body = AccountHeader::body(bytes, BasketEscrow::DISCRIMINATOR)?;
+
+        if body.len() < BasketEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let basket = unsafe { BasketEscrow::load_unchecked(body) };
+
+        if bytes.len() != basket.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(basket)
+    }
+
+    /// Only checks the header fits, so it can be used to write the leg counts of a freshly
+    /// created account.
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let body = AccountHeader::body_mut(bytes, BasketEscrow::DISCRIMINATOR)?;
+
+        if body.len() < BasketEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { BasketEscrow::load_mut_unchecked(body) })
+    }
+}
+
+impl BasketEscrow {
+    /// Maximum number of legs on each side of a basket.
+    pub const MAX_LEGS: usize = 4;
+
+    pub const DISCRIMINATOR: u8 = 4;
+
+    /// Offset of the legs in the account data.
+    const LEGS_OFFSET: usize = AccountHeader::LEN + Self::LEN;
+
+    /// Account size of a basket with the given number of legs, account header included.
+    #[inline(always)]
+    pub const fn space(offer_count: usize, request_count: usize) -> usize {
+        AccountHeader::LEN + Self::LEN + (offer_count + request_count) * Leg::LEN
+    }
+
+    /// Writes the account header into a freshly allocated basket and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, BasketEscrow::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        BasketEscrow::load_mut(bytes)
+    }
+
+    /// Returns the `(offered, requested)` legs stored after the header.
+    #[inline(always)]
+    pub fn legs(bytes: &[u8]) -> Result<(&[Leg], &[Leg]), ProgramError> {
+        let basket = Self::load(bytes)?;
+        let offer_count = basket.offer_count as usize;
+
+        // `load` checked the legs fill the rest of the account, and `Leg` can be read at any
+        // address
+        let legs = unsafe {
+            core::slice::from_raw_parts(
+                bytes.as_ptr().add(Self::LEGS_OFFSET) as *const Leg,
+                offer_count + basket.request_count as usize,
+            )
+        };
+
+        Ok(legs.split_at(offer_count))
+    }
+
+    /// Returns all legs, offered first, for writing.
+    #[inline(always)]
+    pub fn legs_mut(bytes: &mut [u8]) -> Result<&mut [Leg], ProgramError> {
+        let basket = Self::load(bytes)?;
+        let count = basket.offer_count as usize + basket.request_count as usize;
+
+        // See `legs`
+        Ok(unsafe {
+            core::slice::from_raw_parts_mut(
+                bytes.as_mut_ptr().add(Self::LEGS_OFFSET) as *mut Leg,
+                count,
+            )
+        })
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        Self::space(self.offer_count as usize, self.request_count as usize)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        maker: Pubkey,
+        seed: u64,
+        expiry: i64,
+        offer_count: u8,
+        request_count: u8,
+        bump: [u8; 1],
+    ) {
+        self.maker = maker;
+        self.seed = seed.to_le_bytes();
+        self.expiry = expiry.to_le_bytes();
+        self.offer_count = offer_count;
+        self.request_count = request_count;
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    /// An `expiry` of `0` means the basket never expires.
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry().ne(&0) && now > self.expiry()
+    }
+}
+
+/// Header of a milestone escrow, followed in the account data by `milestone_count` tranche
+/// amounts of `mint`, released to the payee in order from the escrow-owned vault.
+#[repr(C)]
+pub struct MilestoneEscrow {
+    pub payer: Pubkey,
+    pub payee: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey, // may release the next tranche
+    pub arbiter: Pubkey,   // settles disputes, zeroed when there is none
+    seed: [u8; 8],
+    deadline: [u8; 8], // the payer can reclaim unreleased tranches after it
+    pub milestone_count: u8,
+    pub released_count: u8,
+    pub bump: [u8; 1],
+    pub disputed: u8,
+    _padding: [u8; 4],
+}
+
+assert_loadable!(MilestoneEscrow);
+
+unsafe impl Loadable for MilestoneEscrow {
+    const LEN: usize = size_of::<Pubkey>() + // payer
+        size_of::<Pubkey>() +   // payee
+        size_of::<Pubkey>() +   // mint
+        size_of::<Pubkey>() +   // authority
+        size_of::<Pubkey>() +   // arbiter
+        size_of::<u64>() +      // seed
+        size_of::<i64>() +      // deadline
+        size_of::<u8>() +       // milestone_count
+        size_of::<u8>() +       // released_count
+        size_of::<[u8;1]>() +   // bump
+        size_of::<u8>() +       // disputed
+        size_of::<[u8;4]>(); // padding
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let body = AccountHeader::body(bytes, MilestoneEscrow::DISCRIMINATOR)?;
+
+        if body.len() < MilestoneEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let escrow = unsafe { MilestoneEscrow::load_unchecked(body) };
+
+        if bytes.len() != Self::space(escrow.milestone_count as usize) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(escrow)
+    }
+
+    /// Only checks the header fits, so it can be used to write the milestone count of a freshly
+    /// created account.
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let body = AccountHeader::body_mut(bytes, MilestoneEscrow::DISCRIMINATOR)?;
+
+        if body.len() < MilestoneEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { MilestoneEscrow::load_mut_unchecked(body) })
+    }
+}
+
+impl MilestoneEscrow {
+    /// Maximum number of milestones of an escrow.
+    pub const MAX_MILESTONES: usize = 8;
+
+    pub const DISCRIMINATOR: u8 = 5;
+
+    /// Offset of the tranche amounts in the account data.
+    const AMOUNTS_OFFSET: usize = AccountHeader::LEN + Self::LEN;
+
+    /// Account size of an escrow with the given number of milestones, account header included.
+    #[inline(always)]
+    pub const fn space(milestone_count: usize) -> usize {
+        AccountHeader::LEN + Self::LEN + milestone_count * size_of::<u64>()
+    }
+
+    /// Writes the account header into a freshly allocated escrow and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(
+            bytes,
+            MilestoneEscrow::DISCRIMINATOR,
+            AccountHeader::VERSION,
+        )?;
+
+        MilestoneEscrow::load_mut(bytes)
+    }
+
+    /// Offset of the `index`th tranche amount, if the escrow has that many milestones.
+    #[inline(always)]
+    fn amount_offset(bytes: &[u8], index: usize) -> Result<usize, ProgramError> {
+        let escrow = Self::load(bytes)?;
+
+        if index >= escrow.milestone_count as usize {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self::AMOUNTS_OFFSET + index * size_of::<u64>())
+    }
+
+    /// Returns the `index`th tranche amount stored after the header.
+    #[inline(always)]
+    pub fn amount(bytes: &[u8], index: usize) -> Result<u64, ProgramError> {
+        let offset = Self::amount_offset(bytes, index)?;
+
+        Ok(u64::from_le_bytes(
+            bytes[offset..offset + size_of::<u64>()].try_into().unwrap(),
+        ))
+    }
+
+    #[inline(always)]
+    pub fn set_amount(bytes: &mut [u8], index: usize, amount: u64) -> Result<(), ProgramError> {
+        let offset = Self::amount_offset(bytes, index)?;
+
+        bytes[offset..offset + size_of::<u64>()].copy_from_slice(&amount.to_le_bytes());
+
+        Ok(())
+    }
+
+    pub fn set_inner(
+        &mut self,
+        payer: Pubkey,
+        payee: Pubkey,
+        mint: Pubkey,
+        authority: Pubkey,
+        seed: u64,
+        bump: [u8; 1],
+    ) {
+        self.payer = payer;
+        self.payee = payee;
+        self.mint = mint;
+        self.authority = authority;
+        self.seed = seed.to_le_bytes();
+        self.released_count = 0;
+        self.bump = bump;
+        self.disputed = 0;
+    }
+
+    #[inline(always)]
+    pub fn set_arbiter(&mut self, arbiter: Pubkey) {
+        self.arbiter = arbiter;
+    }
+
+    #[inline(always)]
+    pub fn set_disputed(&mut self) {
+        self.disputed = 1;
+    }
+
+    #[inline(always)]
+    pub fn set_deadline(&mut self, deadline: i64) {
+        self.deadline = deadline.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_milestone_count(&mut self, milestone_count: u8) {
+        self.milestone_count = milestone_count;
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn deadline(&self) -> i64 {
+        i64::from_le_bytes(self.deadline)
+    }
+
+    #[inline(always)]
+    pub fn is_past_deadline(&self, now: i64) -> bool {
+        now > self.deadline()
+    }
+
+    /// A zeroed `arbiter` means the escrow cannot be disputed.
+    #[inline(always)]
+    pub fn has_arbiter(&self) -> bool {
+        self.arbiter.ne(&Pubkey::default())
+    }
+
+    /// While disputed, only the arbiter can move the vault balance.
+    #[inline(always)]
+    pub fn is_disputed(&self) -> bool {
+        self.disputed.ne(&0)
+    }
+
+    /// Whether the tranche being released is the last one.
+    #[inline(always)]
+    pub fn is_last_milestone(&self) -> bool {
+        self.released_count as usize + 1 == self.milestone_count as usize
+    }
+}
+
+/// Stats of the `[b"market", mint_a, mint_b]` pair, kept up to date by the escrows indexed in
+/// it.
+#[repr(C)]
+pub struct Market {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    open_count: [u8; 8],
+    volume_a: [u8; 8], // `mint_a` filled
+    volume_b: [u8; 8], // `mint_b` paid for it
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(Market);
+
+unsafe impl Loadable for Market {
+    const LEN: usize = size_of::<Pubkey>() + // mint_a
+        size_of::<Pubkey>() +   // mint_b
+        size_of::<u64>() +      // open_count
+        size_of::<u64>() +      // volume_a
+        size_of::<u64>() +      // volume_b
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, Market::DISCRIMINATOR)?;
+
+        if bytes.len() != Market::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Market::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, Market::DISCRIMINATOR)?;
+
+        if bytes.len() != Market::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { Market::load_mut_unchecked(bytes) })
+    }
+}
+
+impl Market {
+    pub const DISCRIMINATOR: u8 = 6;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + Market::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, Market::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        Market::load_mut(bytes)
+    }
+
+    pub fn set_inner(&mut self, mint_a: Pubkey, mint_b: Pubkey, bump: [u8; 1]) {
+        self.mint_a = mint_a;
+        self.mint_b = mint_b;
+        self.open_count = [0; 8];
+        self.volume_a = [0; 8];
+        self.volume_b = [0; 8];
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn open_count(&self) -> u64 {
+        u64::from_le_bytes(self.open_count)
+    }
+
+    #[inline(always)]
+    pub fn volume_a(&self) -> u64 {
+        u64::from_le_bytes(self.volume_a)
+    }
+
+    #[inline(always)]
+    pub fn volume_b(&self) -> u64 {
+        u64::from_le_bytes(self.volume_b)
+    }
+
+    #[inline(always)]
+    pub fn is_pair(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> bool {
+        self.mint_a.eq(mint_a) && self.mint_b.eq(mint_b)
+    }
+
+    #[inline(always)]
+    pub fn record_open(&mut self) -> Result<(), ProgramError> {
+        self.open_count = self
+            .open_count()
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Records an escrow closed without a fill, e.g. refunded.
+    #[inline(always)]
+    pub fn record_close(&mut self) -> Result<(), ProgramError> {
+        self.open_count = self
+            .open_count()
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Records an escrow closed by a fill of `volume_a` for `volume_b`.
+    #[inline(always)]
+    pub fn record_fill(&mut self, volume_a: u64, volume_b: u64) -> Result<(), ProgramError> {
+        self.record_close()?;
+        self.record_partial_fill(volume_a, volume_b)
+    }
+
+    /// Records a fill of `volume_a` for `volume_b` that leaves the escrow open.
+    #[inline(always)]
+    pub fn record_partial_fill(
+        &mut self,
+        volume_a: u64,
+        volume_b: u64,
+    ) -> Result<(), ProgramError> {
+        self.volume_a = self
+            .volume_a()
+            .checked_add(volume_a)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+        self.volume_b = self
+            .volume_b()
+            .checked_add(volume_b)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .to_le_bytes();
+
+        Ok(())
+    }
+}
+
+/// Locks `total` of `mint` for a beneficiary, vesting linearly from `start` to `end` with
+/// nothing claimable before `cliff`.
+#[repr(C)]
+pub struct VestingEscrow {
+    pub funder: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    seed: [u8; 8],
+    start: [u8; 8],
+    cliff: [u8; 8],
+    end: [u8; 8],
+    total: [u8; 8],
+    claimed: [u8; 8],
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(VestingEscrow);
+
+unsafe impl Loadable for VestingEscrow {
+    const LEN: usize = size_of::<Pubkey>() + // funder
+        size_of::<Pubkey>() +   // beneficiary
+        size_of::<Pubkey>() +   // mint
+        size_of::<u64>() +      // seed
+        size_of::<i64>() +      // start
+        size_of::<i64>() +      // cliff
+        size_of::<i64>() +      // end
+        size_of::<u64>() +      // total
+        size_of::<u64>() +      // claimed
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, VestingEscrow::DISCRIMINATOR)?;
+
+        if bytes.len() != VestingEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { VestingEscrow::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, VestingEscrow::DISCRIMINATOR)?;
+
+        if bytes.len() != VestingEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { VestingEscrow::load_mut_unchecked(bytes) })
+    }
+}
+
+impl VestingEscrow {
+    pub const DISCRIMINATOR: u8 = 7;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + VestingEscrow::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, VestingEscrow::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        VestingEscrow::load_mut(bytes)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        funder: Pubkey,
+        beneficiary: Pubkey,
+        mint: Pubkey,
+        seed: u64,
+        total: u64,
+        bump: [u8; 1],
+    ) {
+        self.funder = funder;
+        self.beneficiary = beneficiary;
+        self.mint = mint;
+        self.seed = seed.to_le_bytes();
+        self.total = total.to_le_bytes();
+        self.claimed = [0; 8];
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn start(&self) -> i64 {
+        i64::from_le_bytes(self.start)
+    }
+
+    #[inline(always)]
+    pub fn cliff(&self) -> i64 {
+        i64::from_le_bytes(self.cliff)
+    }
+
+    #[inline(always)]
+    pub fn end(&self) -> i64 {
+        i64::from_le_bytes(self.end)
+    }
+
+    #[inline(always)]
+    pub fn total(&self) -> u64 {
+        u64::from_le_bytes(self.total)
+    }
+
+    #[inline(always)]
+    pub fn claimed(&self) -> u64 {
+        u64::from_le_bytes(self.claimed)
+    }
+
+    #[inline(always)]
+    pub fn set_schedule(&mut self, start: i64, cliff: i64, end: i64) {
+        self.start = start.to_le_bytes();
+        self.cliff = cliff.to_le_bytes();
+        self.end = end.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_claimed(&mut self, claimed: u64) {
+        self.claimed = claimed.to_le_bytes();
+    }
+
+    /// Amount vested at `now`, `total * (now - start) / (end - start)` once past the cliff.
+    pub fn vested(&self, now: i64) -> u64 {
+        if now < self.cliff() {
+            return 0;
+        }
+
+        if now >= self.end() {
+            return self.total();
+        }
+
+        // `start <= cliff <= now < end`, so the elapsed time is non-negative and below the
+        // duration, and the result is below `total`.
+        let elapsed = now.saturating_sub(self.start()) as u128;
+        let duration = self.end().saturating_sub(self.start()) as u128;
+
+        (self.total() as u128 * elapsed / duration) as u64
+    }
+
+    /// Amount vested at `now` that was not claimed yet.
+    #[inline(always)]
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.claimed())
+    }
+
+    #[inline(always)]
+    pub fn is_fully_claimed(&self) -> bool {
+        self.claimed() >= self.total()
+    }
+}
+
+/// Streams `deposited` of `mint` to a recipient at `rate` tokens per second from `start`.
+#[repr(C)]
+pub struct StreamEscrow {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    seed: [u8; 8],
+    start: [u8; 8],
+    rate: [u8; 8], // tokens per second
+    deposited: [u8; 8],
+    withdrawn: [u8; 8],
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(StreamEscrow);
+
+unsafe impl Loadable for StreamEscrow {
+    const LEN: usize = size_of::<Pubkey>() + // sender
+        size_of::<Pubkey>() +   // recipient
+        size_of::<Pubkey>() +   // mint
+        size_of::<u64>() +      // seed
+        size_of::<i64>() +      // start
+        size_of::<u64>() +      // rate
+        size_of::<u64>() +      // deposited
+        size_of::<u64>() +      // withdrawn
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, StreamEscrow::DISCRIMINATOR)?;
+
+        if bytes.len() != StreamEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { StreamEscrow::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, StreamEscrow::DISCRIMINATOR)?;
+
+        if bytes.len() != StreamEscrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { StreamEscrow::load_mut_unchecked(bytes) })
+    }
+}
+
+impl StreamEscrow {
+    pub const DISCRIMINATOR: u8 = 8;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + StreamEscrow::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, StreamEscrow::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        StreamEscrow::load_mut(bytes)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        sender: Pubkey,
+        recipient: Pubkey,
+        mint: Pubkey,
+        seed: u64,
+        bump: [u8; 1],
+    ) {
+        self.sender = sender;
+        self.recipient = recipient;
+        self.mint = mint;
+        self.seed = seed.to_le_bytes();
+        self.withdrawn = [0; 8];
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn start(&self) -> i64 {
+        i64::from_le_bytes(self.start)
+    }
+
+    #[inline(always)]
+    pub fn rate(&self) -> u64 {
+        u64::from_le_bytes(self.rate)
+    }
+
+    #[inline(always)]
+    pub fn deposited(&self) -> u64 {
+        u64::from_le_bytes(self.deposited)
+    }
+
+    #[inline(always)]
+    pub fn withdrawn(&self) -> u64 {
+        u64::from_le_bytes(self.withdrawn)
+    }
+
+    #[inline(always)]
+    pub fn set_stream(&mut self, start: i64, rate: u64, deposited: u64) {
+        self.start = start.to_le_bytes();
+        self.rate = rate.to_le_bytes();
+        self.deposited = deposited.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_withdrawn(&mut self, withdrawn: u64) {
+        self.withdrawn = withdrawn.to_le_bytes();
+    }
+
+    /// Amount streamed to the recipient by `now`, capped at `deposited`.
+    pub fn accrued(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start()).max(0) as u64;
+
+        elapsed.saturating_mul(self.rate()).min(self.deposited())
+    }
+
+    /// Amount accrued by `now` that the recipient did not withdraw yet.
+    #[inline(always)]
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.accrued(now).saturating_sub(self.withdrawn())
+    }
+
+    #[inline(always)]
+    pub fn is_depleted(&self) -> bool {
+        self.withdrawn() >= self.deposited()
+    }
+}
+
+/// Marks a signed order's `nonce` as used, see `FillOrder`.
+#[repr(C)]
+pub struct OrderNonce {
+    pub maker: Pubkey,
+    nonce: [u8; 8],
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(OrderNonce);
+
+unsafe impl Loadable for OrderNonce {
+    const LEN: usize = size_of::<Pubkey>() + // maker
+        size_of::<u64>() +      // nonce
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, OrderNonce::DISCRIMINATOR)?;
+
+        if bytes.len() != OrderNonce::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { OrderNonce::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, OrderNonce::DISCRIMINATOR)?;
+
+        if bytes.len() != OrderNonce::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { OrderNonce::load_mut_unchecked(bytes) })
+    }
+}
+
+impl OrderNonce {
+    pub const DISCRIMINATOR: u8 = 9;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + OrderNonce::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, OrderNonce::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        OrderNonce::load_mut(bytes)
+    }
+
+    #[inline(always)]
+    pub fn nonce(&self) -> u64 {
+        u64::from_le_bytes(self.nonce)
+    }
+
+    pub fn set_inner(&mut self, maker: Pubkey, nonce: u64, bump: [u8; 1]) {
+        self.maker = maker;
+        self.nonce = nonce.to_le_bytes();
+        self.bump = bump;
+    }
+}
+
+/// Alternative terms proposed on an `Escrow`, backed by the proposer's `token_b` deposit held
+/// in the counter offer's ATA.
+#[repr(C)]
+pub struct CounterOffer {
+    pub escrow: Pubkey,
+    pub proposer: Pubkey,
+    amount: [u8; 8],  // `token_a` requested from the vault
+    deposit: [u8; 8], // `token_b` offered for it
+    pub bump: [u8; 1],
+}
+
+assert_loadable!(CounterOffer);
+
+unsafe impl Loadable for CounterOffer {
+    const LEN: usize = size_of::<Pubkey>() + // escrow
+        size_of::<Pubkey>() +   // proposer
+        size_of::<u64>() +      // amount
+        size_of::<u64>() +      // deposit
+        size_of::<[u8;1]>(); // bump
+
+    #[inline(always)]
+    fn load(bytes: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = AccountHeader::body(bytes, CounterOffer::DISCRIMINATOR)?;
+
+        if bytes.len() != CounterOffer::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { CounterOffer::load_unchecked(bytes) })
+    }
+
+    #[inline(always)]
+    fn load_mut(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = AccountHeader::body_mut(bytes, CounterOffer::DISCRIMINATOR)?;
+
+        if bytes.len() != CounterOffer::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { CounterOffer::load_mut_unchecked(bytes) })
+    }
+}
+
+impl CounterOffer {
+    pub const DISCRIMINATOR: u8 = 10;
+
+    /// Space allocated for the account, header included.
+    pub const SPACE: usize = AccountHeader::LEN + CounterOffer::LEN;
+
+    /// Writes the header into a freshly allocated account and returns its body.
+    #[inline(always)]
+    pub fn init(bytes: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        AccountHeader::init(bytes, CounterOffer::DISCRIMINATOR, AccountHeader::VERSION)?;
+
+        CounterOffer::load_mut(bytes)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        escrow: Pubkey,
+        proposer: Pubkey,
+        amount: u64,
+        deposit: u64,
+        bump: [u8; 1],
+    ) {
+        self.escrow = escrow;
+        self.proposer = proposer;
+        self.amount = amount.to_le_bytes();
+        self.deposit = deposit.to_le_bytes();
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    #[inline(always)]
+    pub fn deposit(&self) -> u64 {
+        u64::from_le_bytes(self.deposit)
+    }
 }
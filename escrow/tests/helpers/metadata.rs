@@ -0,0 +1,58 @@
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::{pubkey, rent::Rent};
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// A minimal borsh-encoded Metaplex `Metadata` account of `mint`, optionally part of a
+/// `(collection, verified)`.
+pub fn keyed_account_for_metadata(
+    mint: &Pubkey,
+    collection: Option<(Pubkey, bool)>,
+) -> (Pubkey, Account) {
+    let mut data = vec![4]; // Key::MetadataV1
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // update_authority
+    data.extend_from_slice(mint.as_ref());
+    for field in ["NFT", "NFT", "https://example.com/nft.json"] {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(field.as_bytes());
+    }
+    data.extend_from_slice(&0u16.to_le_bytes()); // seller_fee_basis_points
+    data.push(1); // creators
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+    data.extend_from_slice(&[1, 100]);
+    data.extend_from_slice(&[0, 1]); // primary_sale_happened, is_mutable
+    data.extend_from_slice(&[1, 255]); // edition_nonce
+    data.extend_from_slice(&[1, 0]); // token_standard: NonFungible
+    match collection {
+        Some((key, verified)) => {
+            data.extend_from_slice(&[1, verified as u8]);
+            data.extend_from_slice(key.as_ref());
+        }
+        None => data.push(0),
+    }
+
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: TOKEN_METADATA_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    (metadata_pda(mint), account)
+}
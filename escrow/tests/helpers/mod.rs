@@ -1,5 +1,10 @@
-pub mod token;
-pub use token::*;
+pub use test_harness::{config::*, program_path, store::*, token::*};
 
-pub mod store;
-pub use store::*;
+pub mod metadata;
+pub use metadata::*;
+
+pub mod order;
+pub use order::*;
+
+pub mod oracle;
+pub use oracle::*;
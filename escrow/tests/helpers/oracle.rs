@@ -0,0 +1,40 @@
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::{pubkey, rent::Rent};
+
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+pub const PRICE_UPDATE_V2_LEN: usize = 8 + 32 + 2 + 84 + 8;
+
+/// A fully verified Pyth `PriceUpdateV2` account quoting `price * 10^exponent`.
+pub fn keyed_account_for_price_update(
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+) -> (Pubkey, Account) {
+    let mut data = Vec::with_capacity(PRICE_UPDATE_V2_LEN);
+    data.extend_from_slice(&[34, 241, 35, 99, 157, 126, 244, 205]); // discriminator
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // write_authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&[0xab; 32]); // feed_id
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&conf.to_le_bytes());
+    data.extend_from_slice(&exponent.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes()); // prev_publish_time
+    data.extend_from_slice(&price.to_le_bytes()); // ema_price
+    data.extend_from_slice(&conf.to_le_bytes()); // ema_conf
+    data.extend_from_slice(&0u64.to_le_bytes()); // posted_slot
+    data.resize(PRICE_UPDATE_V2_LEN, 0);
+
+    let account = Account {
+        lamports: Rent::default().minimum_balance(PRICE_UPDATE_V2_LEN),
+        data,
+        owner: PYTH_RECEIVER_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    (Pubkey::new_unique(), account)
+}
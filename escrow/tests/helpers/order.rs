@@ -0,0 +1,83 @@
+use solana_account::Account;
+use solana_instruction::{BorrowedAccountMeta, BorrowedInstruction, Instruction};
+use solana_instructions_sysvar::{construct_instructions_data, ID as INSTRUCTIONS_SYSVAR_ID};
+use solana_pubkey::Pubkey;
+use solana_sdk::{rent::Rent, sysvar};
+
+pub const ORDER_NONCE_LEN: usize = super::ACCOUNT_HEADER_LEN + 32 + 8 + 1;
+
+pub fn delegate_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"delegate"], program_id).0
+}
+
+pub fn nonce_pda(program_id: &Pubkey, maker: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"nonce", maker.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// The message a maker signs off-chain, see `escrow::instructions::FillOrderInstructionData`.
+#[allow(clippy::too_many_arguments)]
+pub fn order_message(
+    program_id: &Pubkey,
+    maker: &Pubkey,
+    a_mint: &Pubkey,
+    b_mint: &Pubkey,
+    amount: u64,
+    receive: u64,
+    seed: u64,
+    expiry: i64,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 * 4 + 8 * 5);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(maker.as_ref());
+    message.extend_from_slice(a_mint.as_ref());
+    message.extend_from_slice(b_mint.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&receive.to_le_bytes());
+    message.extend_from_slice(&seed.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message
+}
+
+/// Mollusk doesn't populate the Instructions sysvar, so build the one the runtime would
+/// expose while executing `instructions[current_index]`.
+pub fn keyed_account_for_instructions_sysvar(
+    instructions: &[&Instruction],
+    current_index: u16,
+) -> (Pubkey, Account) {
+    let borrowed = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect::<Vec<_>>();
+
+    let mut data = construct_instructions_data(&borrowed);
+    let len = data.len();
+    data[len - 2..].copy_from_slice(&current_index.to_le_bytes());
+
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: sysvar::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    (INSTRUCTIONS_SYSVAR_ID, account)
+}
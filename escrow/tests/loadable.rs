@@ -0,0 +1,440 @@
+use escrow::{
+    loadable::Loadable,
+    state::{
+        AccountHeader, BasketEscrow, Config, CounterOffer, Escrow, EscrowV0, Leg, Market,
+        MilestoneEscrow, OrderNonce, Referral, StreamEscrow, VestingEscrow,
+    },
+};
+use pinocchio::program_error::ProgramError;
+
+/// A zeroed buffer of `len` bytes starting one byte past an 8-byte boundary, so that no `u64`
+/// could be read in place from it.
+fn misaligned(storage: &mut Vec<u64>, len: usize) -> &mut [u8] {
+    storage.resize(len / 8 + 2, 0);
+    let bytes = unsafe {
+        core::slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut u8, storage.len() * 8)
+    };
+    let bytes = &mut bytes[1..len + 1];
+
+    assert_ne!(bytes.as_ptr() as usize % core::mem::align_of::<u64>(), 0);
+
+    bytes
+}
+
+fn write_escrow(escrow: &mut Escrow) {
+    escrow.set_inner([1; 32], [2; 32], [3; 32], u64::MAX - 1, 1_000_000, [254]);
+    escrow.set_expiry(-42);
+    escrow.set_min_fill(500);
+    escrow.set_oracle([4; 32], 150);
+}
+
+fn assert_escrow(escrow: &Escrow) {
+    assert_eq!(escrow.maker, [1; 32]);
+    assert_eq!(escrow.token_a_mint, [2; 32]);
+    assert_eq!(escrow.token_b_mint, [3; 32]);
+    assert_eq!(escrow.seed(), u64::MAX - 1);
+    assert_eq!(escrow.receive(), 1_000_000);
+    assert_eq!(escrow.bump, [254]);
+    assert_eq!(escrow.expiry(), -42);
+    assert_eq!(escrow.min_fill(), 500);
+    assert_eq!(escrow.price_feed, [4; 32]);
+    assert_eq!(escrow.spread_bps(), 150);
+}
+
+#[test]
+fn test_escrow_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Escrow::SPACE);
+
+    write_escrow(Escrow::init(bytes).unwrap());
+
+    assert_eq!(Escrow::version(bytes).unwrap(), Escrow::VERSION);
+    assert_escrow(Escrow::load(bytes).unwrap());
+
+    // Integers are stored little-endian right after the header and the six 32-byte fields
+    let receive = AccountHeader::LEN + 32 * 6;
+    assert_eq!(bytes[receive..receive + 8], 1_000_000u64.to_le_bytes());
+    assert_eq!(
+        bytes[receive + 8..receive + 16],
+        (u64::MAX - 1).to_le_bytes()
+    );
+}
+
+#[test]
+fn test_escrow_misaligned_load_mut() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Escrow::SPACE);
+
+    Escrow::init(bytes).unwrap();
+    write_escrow(Escrow::load_mut(bytes).unwrap());
+    Escrow::load_mut(bytes).unwrap().set_receive(7);
+
+    assert_eq!(Escrow::load(bytes).unwrap().receive(), 7);
+}
+
+/// Writes an escrow as the deployed program laid it out, before the account header and the
+/// optional terms: maker, both mints, receive, seed and bump.
+fn write_legacy_escrow(bytes: &mut [u8]) {
+    bytes[..32].copy_from_slice(&[1; 32]);
+    bytes[32..64].copy_from_slice(&[2; 32]);
+    bytes[64..96].copy_from_slice(&[3; 32]);
+    bytes[96..104].copy_from_slice(&1_000_000u64.to_le_bytes());
+    bytes[104..112].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+    bytes[112] = 254;
+}
+
+#[test]
+fn test_escrow_misaligned_legacy() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, EscrowV0::LEN);
+
+    write_legacy_escrow(bytes);
+
+    assert_eq!(EscrowV0::LEN, 113);
+    assert_eq!(Escrow::version(bytes).unwrap(), 0);
+
+    let legacy = EscrowV0::load(bytes).unwrap();
+    assert_eq!(legacy.maker, [1; 32]);
+    assert_eq!(legacy.token_b_mint, [3; 32]);
+    assert_eq!(legacy.receive(), 1_000_000);
+    assert_eq!(legacy.seed(), u64::MAX - 1);
+    assert_eq!(legacy.bump, [254]);
+
+    // Legacy escrows have to be migrated before use
+    assert_eq!(
+        Escrow::load(bytes).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_escrow_misaligned_migrate() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Escrow::SPACE);
+
+    // Whatever follows the legacy body is overwritten
+    bytes.fill(0xaa);
+    write_legacy_escrow(bytes);
+    Escrow::migrate(bytes, 0).unwrap();
+
+    assert_eq!(Escrow::version(bytes).unwrap(), Escrow::VERSION);
+
+    let escrow = Escrow::load(bytes).unwrap();
+    assert_eq!(escrow.maker, [1; 32]);
+    assert_eq!(escrow.token_a_mint, [2; 32]);
+    assert_eq!(escrow.token_b_mint, [3; 32]);
+    assert_eq!(escrow.receive(), 1_000_000);
+    assert_eq!(escrow.seed(), u64::MAX - 1);
+    assert_eq!(escrow.bump, [254]);
+
+    // New fields get their defaults
+    assert!(escrow.can_be_taken_by(&[9; 32]));
+    assert!(!escrow.is_oracle_priced());
+    assert!(!escrow.is_hashlocked());
+    assert!(!escrow.is_indexed());
+    assert_eq!(escrow.expiry(), 0);
+    assert_eq!(escrow.min_fill(), 0);
+    assert_eq!(escrow.spread_bps(), 0);
+
+    // Current escrows can't be migrated again
+    assert_eq!(
+        Escrow::migrate(bytes, Escrow::VERSION).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_escrow_misaligned_load_unchecked() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Escrow::SPACE);
+
+    write_escrow(Escrow::init(bytes).unwrap());
+
+    assert_escrow(unsafe { Escrow::load_unchecked(&bytes[AccountHeader::LEN..]) });
+}
+
+#[test]
+fn test_escrow_load_rejects_invalid_data() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Escrow::SPACE);
+
+    // Uninitialized
+    assert_eq!(
+        Escrow::load(bytes).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+
+    // Another account type
+    AccountHeader::init(bytes, 2, AccountHeader::VERSION).unwrap();
+    assert_eq!(
+        Escrow::load(bytes).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+
+    // Wrong size
+    assert_eq!(
+        Escrow::load(&bytes[..Escrow::SPACE - 1]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        AccountHeader::load(&bytes[..AccountHeader::LEN - 1]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_config_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Config::SPACE);
+
+    Config::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], [2; 32], 250, 4_000, [254]);
+    Config::load_mut(bytes).unwrap().set_paused(true);
+
+    let config = Config::load(bytes).unwrap();
+    assert_eq!(config.admin, [1; 32]);
+    assert_eq!(config.fee_recipient, [2; 32]);
+    assert_eq!(config.fee_bps(), 250);
+    assert_eq!(config.referral_share_bps(), 4_000);
+    assert!(config.is_paused());
+    assert_eq!(config.bump, [254]);
+    assert_eq!(config.fee(1_000_000).unwrap(), 25_000);
+    assert_eq!(config.split_fee(25_000).unwrap(), (15_000, 10_000));
+}
+
+#[test]
+fn test_referral_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Referral::SPACE);
+
+    Referral::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], [2; 32], [254]);
+    Referral::load_mut(bytes)
+        .unwrap()
+        .record(u64::MAX - 1, 7)
+        .unwrap();
+
+    let referral = Referral::load(bytes).unwrap();
+    assert_eq!(referral.referrer, [1; 32]);
+    assert_eq!(referral.mint, [2; 32]);
+    assert_eq!(referral.volume(), u64::MAX - 1);
+    assert_eq!(referral.fees(), 7);
+    assert_eq!(referral.bump, [254]);
+}
+
+#[test]
+fn test_basket_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, BasketEscrow::space(2, 1));
+
+    BasketEscrow::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], u64::MAX - 1, -42, 2, 1, [254]);
+
+    let basket = BasketEscrow::load(bytes).unwrap();
+    assert_eq!(basket.maker, [1; 32]);
+    assert_eq!(basket.seed(), u64::MAX - 1);
+    assert_eq!(basket.expiry(), -42);
+    assert_eq!(basket.offer_count, 2);
+    assert_eq!(basket.request_count, 1);
+    assert_eq!(basket.bump, [254]);
+
+    // The account has to fit exactly the legs of the header
+    assert_eq!(
+        BasketEscrow::load(&bytes[..BasketEscrow::space(2, 0)]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_basket_misaligned_legs() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, BasketEscrow::space(2, 1));
+
+    BasketEscrow::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], 0, 0, 2, 1, [254]);
+
+    for (i, leg) in BasketEscrow::legs_mut(bytes)
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        leg.mint = [i as u8 + 2; 32];
+        leg.set_amount(u64::MAX - i as u64);
+    }
+
+    let (offered, requested) = BasketEscrow::legs(bytes).unwrap();
+    assert_eq!(offered.len(), 2);
+    assert_eq!(requested.len(), 1);
+    assert_eq!(offered[1].mint, [3; 32]);
+    assert_eq!(offered[1].amount(), u64::MAX - 1);
+    assert_eq!(requested[0].mint, [4; 32]);
+    assert_eq!(requested[0].amount(), u64::MAX - 2);
+
+    // Legs follow the header back to back, amounts stored little-endian after the mint
+    let amount = BasketEscrow::space(0, 0) + Leg::LEN + 32;
+    assert_eq!(bytes[amount..amount + 8], (u64::MAX - 1).to_le_bytes());
+}
+
+#[test]
+fn test_milestone_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, MilestoneEscrow::space(3));
+
+    let escrow = MilestoneEscrow::init(bytes).unwrap();
+    escrow.set_inner([1; 32], [2; 32], [3; 32], [4; 32], u64::MAX - 1, [254]);
+    escrow.set_arbiter([5; 32]);
+    escrow.set_deadline(-42);
+    escrow.set_milestone_count(3);
+    escrow.set_disputed();
+
+    let escrow = MilestoneEscrow::load(bytes).unwrap();
+    assert_eq!(escrow.payer, [1; 32]);
+    assert_eq!(escrow.payee, [2; 32]);
+    assert_eq!(escrow.mint, [3; 32]);
+    assert_eq!(escrow.authority, [4; 32]);
+    assert_eq!(escrow.arbiter, [5; 32]);
+    assert_eq!(escrow.seed(), u64::MAX - 1);
+    assert_eq!(escrow.deadline(), -42);
+    assert_eq!(escrow.milestone_count, 3);
+    assert_eq!(escrow.released_count, 0);
+    assert_eq!(escrow.bump, [254]);
+    assert_eq!(escrow.disputed, 1);
+
+    assert_eq!(
+        MilestoneEscrow::load(&bytes[..MilestoneEscrow::space(2)]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_milestone_misaligned_amounts() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, MilestoneEscrow::space(3));
+
+    MilestoneEscrow::init(bytes).unwrap().set_milestone_count(3);
+
+    for index in 0..3 {
+        MilestoneEscrow::set_amount(bytes, index, u64::MAX - index as u64).unwrap();
+    }
+
+    assert_eq!(MilestoneEscrow::amount(bytes, 0).unwrap(), u64::MAX);
+    assert_eq!(MilestoneEscrow::amount(bytes, 2).unwrap(), u64::MAX - 2);
+
+    // Amounts follow the header back to back, stored little-endian
+    let amount = MilestoneEscrow::space(1);
+    assert_eq!(bytes[amount..amount + 8], (u64::MAX - 1).to_le_bytes());
+
+    assert_eq!(
+        MilestoneEscrow::amount(bytes, 3).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        MilestoneEscrow::set_amount(bytes, 3, 1).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn test_market_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, Market::SPACE);
+
+    Market::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], [2; 32], [254]);
+
+    let market = Market::load_mut(bytes).unwrap();
+    market.record_open().unwrap();
+    market.record_open().unwrap();
+    market.record_fill(u64::MAX - 1, 3).unwrap();
+
+    let market = Market::load(bytes).unwrap();
+    assert!(market.is_pair(&[1; 32], &[2; 32]));
+    assert_eq!(market.open_count(), 1);
+    assert_eq!(market.volume_a(), u64::MAX - 1);
+    assert_eq!(market.volume_b(), 3);
+    assert_eq!(market.bump, [254]);
+}
+
+#[test]
+fn test_vesting_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, VestingEscrow::SPACE);
+
+    let escrow = VestingEscrow::init(bytes).unwrap();
+    escrow.set_inner([1; 32], [2; 32], [3; 32], u64::MAX - 1, 1_000, [254]);
+    escrow.set_schedule(-100, 0, 100);
+    escrow.set_claimed(250);
+
+    let escrow = VestingEscrow::load(bytes).unwrap();
+    assert_eq!(escrow.funder, [1; 32]);
+    assert_eq!(escrow.beneficiary, [2; 32]);
+    assert_eq!(escrow.mint, [3; 32]);
+    assert_eq!(escrow.seed(), u64::MAX - 1);
+    assert_eq!(escrow.start(), -100);
+    assert_eq!(escrow.cliff(), 0);
+    assert_eq!(escrow.end(), 100);
+    assert_eq!(escrow.total(), 1_000);
+    assert_eq!(escrow.claimed(), 250);
+    assert_eq!(escrow.bump, [254]);
+    assert_eq!(escrow.claimable(0), 250);
+}
+
+#[test]
+fn test_stream_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, StreamEscrow::SPACE);
+
+    let escrow = StreamEscrow::init(bytes).unwrap();
+    escrow.set_inner([1; 32], [2; 32], [3; 32], u64::MAX - 1, [254]);
+    escrow.set_stream(-100, 5, 1_000);
+    escrow.set_withdrawn(200);
+
+    let escrow = StreamEscrow::load(bytes).unwrap();
+    assert_eq!(escrow.sender, [1; 32]);
+    assert_eq!(escrow.recipient, [2; 32]);
+    assert_eq!(escrow.mint, [3; 32]);
+    assert_eq!(escrow.seed(), u64::MAX - 1);
+    assert_eq!(escrow.start(), -100);
+    assert_eq!(escrow.rate(), 5);
+    assert_eq!(escrow.deposited(), 1_000);
+    assert_eq!(escrow.withdrawn(), 200);
+    assert_eq!(escrow.bump, [254]);
+    assert_eq!(escrow.withdrawable(0), 300);
+}
+
+#[test]
+fn test_order_nonce_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, OrderNonce::SPACE);
+
+    OrderNonce::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], u64::MAX - 1, [254]);
+
+    let nonce = OrderNonce::load(bytes).unwrap();
+    assert_eq!(nonce.maker, [1; 32]);
+    assert_eq!(nonce.nonce(), u64::MAX - 1);
+    assert_eq!(nonce.bump, [254]);
+}
+
+#[test]
+fn test_counter_offer_misaligned_round_trip() {
+    let mut storage = Vec::new();
+    let bytes = misaligned(&mut storage, CounterOffer::SPACE);
+
+    CounterOffer::init(bytes)
+        .unwrap()
+        .set_inner([1; 32], [2; 32], u64::MAX - 1, 7, [254]);
+
+    let counter = CounterOffer::load(bytes).unwrap();
+    assert_eq!(counter.escrow, [1; 32]);
+    assert_eq!(counter.proposer, [2; 32]);
+    assert_eq!(counter.amount(), u64::MAX - 1);
+    assert_eq!(counter.deposit(), 7);
+    assert_eq!(counter.bump, [254]);
+}
@@ -5,12 +5,18 @@ use mollusk_svm::{
     Mollusk, MolluskContext,
 };
 use solana_account::Account;
+use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_instructions_sysvar::ID as INSTRUCTIONS_SYSVAR_ID;
 use solana_pubkey::Pubkey;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
     pubkey,
+    signature::{Keypair, Signer},
 };
+use solana_sha256_hasher::hash;
+use solana_system_interface::{instruction as system_instruction, program as system_program};
 
 pub mod helpers;
 pub use helpers::*;
@@ -28,8 +34,75 @@ pub const PROGRAM_ID: Pubkey = pubkey!("Hss68bDHZjSwXberD14diFDYXDiTBrSYdkUjED3v
 
 const DECIMALS: u8 = 6;
 
+// Error codes, see `escrow::errors::EscrowError`
+const OFFER_EXPIRED: u32 = 0;
+const OFFER_NOT_EXPIRED: u32 = 1;
+const INVALID_EXPIRY: u32 = 2;
+const INVALID_TAKER: u32 = 3;
+const INVALID_FEE_BPS: u32 = 4;
+const UNAUTHORIZED: u32 = 5;
+const PROGRAM_PAUSED: u32 = 6;
+const INVALID_LEGS: u32 = 7;
+const NOT_AN_NFT: u32 = 8;
+const INVALID_COLLECTION: u32 = 9;
+const INVALID_SIGNATURE: u32 = 10;
+const ORDER_ALREADY_FILLED: u32 = 11;
+const STALE_PRICE: u32 = 12;
+const INVALID_PRICE: u32 = 13;
+const TOTAL_NOT_REACHED: u32 = 14;
+const INVALID_MILESTONES: u32 = 15;
+const ESCROW_DISPUTED: u32 = 16;
+const NOT_DISPUTED: u32 = 17;
+const INVALID_SCHEDULE: u32 = 18;
+const NOTHING_TO_CLAIM: u32 = 19;
+const INVALID_PREIMAGE: u32 = 20;
+const HASHLOCKED: u32 = 21;
+const BELOW_MIN_FILL: u32 = 22;
+const MAX_PAY_EXCEEDED: u32 = 23;
+const MIN_RECEIVE_NOT_MET: u32 = 24;
+const ALREADY_MIGRATED: u32 = 25;
+const INVALID_REFERRER: u32 = 26;
+
+// See `escrow::instructions::make`
+const NFT_TOKEN_A: u8 = 0b01;
+const NFT_TOKEN_B: u8 = 0b10;
+
+// See `escrow::instructions::TakeManyInstructionData`
+const PRICE_SCALE: u64 = 1_000_000_000;
+
+// See `escrow::state::Escrow`
+const ESCROW_DISCRIMINATOR: u8 = 1;
+const ESCROW_BODY_LEN: usize = 32 * 6 + 8 * 4 + 2 + 1 + 1;
+const ESCROW_RECEIVE_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 6;
+// Escrows created before the account header: maker, both mints, receive, seed and bump
+const LEGACY_ESCROW_LEN: usize = 32 * 3 + 8 + 8 + 1;
+
+// See `escrow::state::CounterOffer`
+const COUNTER_OFFER_LEN: usize = ACCOUNT_HEADER_LEN + 32 + 32 + 8 + 8 + 1;
+
+// See `escrow::state::MilestoneEscrow`
+const MILESTONE_HEADER_LEN: usize = ACCOUNT_HEADER_LEN + 32 * 5 + 8 + 8 + 1 + 1 + 1 + 1 + 4;
+const MILESTONE_RELEASED_COUNT_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 5 + 8 + 8 + 1;
+const MILESTONE_DISPUTED_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 5 + 8 + 8 + 1 + 1 + 1;
+
+// See `escrow::state::VestingEscrow`
+const VESTING_ESCROW_LEN: usize = ACCOUNT_HEADER_LEN + 32 * 3 + 8 * 6 + 1;
+const VESTING_CLAIMED_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 3 + 8 * 5;
+
+// See `escrow::state::StreamEscrow`
+const STREAM_ESCROW_LEN: usize = ACCOUNT_HEADER_LEN + 32 * 3 + 8 * 5 + 1;
+const STREAM_WITHDRAWN_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 3 + 8 * 4;
+
+// See `escrow::state::Market`
+const MARKET_LEN: usize = ACCOUNT_HEADER_LEN + 32 * 2 + 8 * 3 + 1;
+const MARKET_OPEN_COUNT_OFFSET: usize = ACCOUNT_HEADER_LEN + 32 * 2;
+
+// See `escrow::state::BasketEscrow`
+const BASKET_HEADER_LEN: usize = ACCOUNT_HEADER_LEN + 32 + 8 + 8 + 1 + 1 + 1 + 5;
+const BASKET_LEG_LEN: usize = 32 + 8;
+
 fn prelude() -> (MolluskContext<InMemoryAccountStore>, Vec<(Pubkey, Account)>) {
-    let mollusk = Mollusk::new(&PROGRAM_ID, "./target/deploy/escrow");
+    let mollusk = Mollusk::new(&PROGRAM_ID, &program_path("escrow"));
     let mut context = mollusk.with_context(InMemoryAccountStore::default());
 
     // Add the SPL Token Program
@@ -47,15 +120,16 @@ fn prelude() -> (MolluskContext<InMemoryAccountStore>, Vec<(Pubkey, Account)>) {
     (context, v)
 }
 
-#[test]
-fn test_make() {
-    let prelude = prelude();
-
-    let context = prelude.0;
-    let prelude_accounts = prelude.1;
-
-    let [token_program, associated_token_program, system_program] = prelude_accounts.as_slice()
-    else {
+/// Creates a funded maker, both mints and the maker's `mint_a` ATA, and stores them alongside
+/// the (not yet existing) escrow and vault accounts and a fee-less, unpaused `Config`. Returns
+/// the accounts in `make` order.
+fn setup_make(
+    context: &MolluskContext<InMemoryAccountStore>,
+    prelude_accounts: &[(Pubkey, Account)],
+    starting_tokens_amount: u64,
+    seed: u64,
+) -> [(Pubkey, Account); 10] {
+    let [token_program, associated_token_program, system_program] = prelude_accounts else {
         panic!("Could not fetch prelude accounts");
     };
 
@@ -68,7 +142,6 @@ fn test_make() {
     let (mint_b_pubkey, mint_b_account) = keyed_account_for_mint_default(&maker, None, DECIMALS);
 
     // Create maker_ata for a_mint
-    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
     let (maker_ata_pubkey, maker_ata_account) = keyed_account_for_associated_token_account(
         &mint_a_pubkey,
         &maker,
@@ -76,46 +149,535 @@ fn test_make() {
         Some(token_program.0),
     );
 
-    let seed = random_u64();
-    let escrow_pubkey = Pubkey::find_program_address(
-        &[b"escrow", maker.as_ref(), seed.to_le_bytes().as_ref()],
-        &PROGRAM_ID,
-    );
+    let (escrow_pubkey, _) = escrow_pda(&maker, seed);
 
     // Find Associated Token Account
-    let escrow_ata_pubkey = Pubkey::find_program_address(
-        &[
-            escrow_pubkey.0.as_ref(),
-            token_program.0.as_ref(),
-            mint_a_pubkey.as_ref(),
-        ],
-        &associated_token_program.0,
-    );
+    let escrow_ata_pubkey = associated_token_address(&escrow_pubkey, &mint_a_pubkey);
 
-    println!("Associated addr: {}", &escrow_ata_pubkey.0);
+    let config = keyed_account_for_config(
+        &PROGRAM_ID,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        0,
+        0,
+    );
 
     let accounts = [
         (maker, maker_account),
-        (escrow_pubkey.0, Account::default()),
+        (escrow_pubkey, Account::default()),
         (mint_a_pubkey, mint_a_account),
         (mint_b_pubkey, mint_b_account),
         (maker_ata_pubkey, maker_ata_account),
-        (escrow_ata_pubkey.0, Account::default()),
-        (system_program.0.clone(), system_program.1.clone()),
-        (token_program.0.clone(), token_program.1.clone()),
-        (
-            associated_token_program.0.clone(),
-            associated_token_program.1.clone(),
-        ),
+        (escrow_ata_pubkey, Account::default()),
+        config,
+        system_program.clone(),
+        token_program.clone(),
+        associated_token_program.clone(),
     ];
 
     {
         let mut store = context.account_store.borrow_mut();
         for (pubkey, account) in &accounts {
-            store.store_account(pubkey.clone(), account.clone());
+            store.store_account(*pubkey, account.clone());
         }
     } // store is dropped here, releasing the mutable borrow
 
+    accounts
+}
+
+/// Sends `amount` more tokens to `token_account` from outside the program, as anyone could.
+fn donate(context: &MolluskContext<InMemoryAccountStore>, token_account: &Pubkey, amount: u64) {
+    let mut store = context.account_store.borrow_mut();
+    let mut account = store
+        .get_account(token_account)
+        .expect("Could not fetch token account");
+
+    let balance = u64::from_le_bytes(account.data[64..72].try_into().unwrap());
+    account.data[64..72].copy_from_slice(&(balance + amount).to_le_bytes());
+
+    store.store_account(*token_account, account);
+}
+
+struct ConfigFixture {
+    config: Pubkey,
+    admin: Pubkey,
+    fee_recipient: Pubkey,
+}
+
+/// Stores an initialized protocol `Config` charging `fee_bps` on settlement, of which
+/// `referral_share_bps` goes to the referrer on routed fills.
+fn setup_config(
+    context: &MolluskContext<InMemoryAccountStore>,
+    fee_bps: u16,
+    referral_share_bps: u16,
+) -> ConfigFixture {
+    let admin = Pubkey::new_unique();
+    let (fee_recipient, fee_recipient_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (config, config_account) = keyed_account_for_config(
+        &PROGRAM_ID,
+        &admin,
+        &fee_recipient,
+        fee_bps,
+        referral_share_bps,
+    );
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(fee_recipient, fee_recipient_account);
+        store.store_account(config, config_account);
+    }
+
+    ConfigFixture {
+        config,
+        admin,
+        fee_recipient,
+    }
+}
+
+/// Stores a paused `Config` along with its funded admin, so the test can unpause it later.
+fn setup_paused_config(context: &MolluskContext<InMemoryAccountStore>) -> ConfigFixture {
+    let config = setup_config(context, 0, 0);
+
+    let (_, admin_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(config.admin, admin_account);
+
+    set_paused(
+        context,
+        &[config.admin, config.config],
+        true,
+        &[Check::success()],
+    );
+
+    config
+}
+
+struct BasketFixture {
+    maker: Pubkey,
+    taker: Pubkey,
+    basket: Pubkey,
+    seed: u64,
+    offered: Vec<(Pubkey, u64)>,   // (mint, amount)
+    requested: Vec<(Pubkey, u64)>, // (mint, amount)
+    config: ConfigFixture,
+    system_program: Pubkey,
+    token_program: Pubkey,
+    associated_token_program: Pubkey,
+}
+
+/// Creates a maker holding each `offered` amount of a fresh mint, and a taker holding each
+/// `requested` amount of another fresh mint, along with a `Config` charging `fee_bps`.
+fn setup_basket(
+    context: &MolluskContext<InMemoryAccountStore>,
+    prelude_accounts: &[(Pubkey, Account)],
+    offered: &[u64],
+    requested: &[u64],
+    fee_bps: u16,
+) -> BasketFixture {
+    let [token_program, associated_token_program, system_program] = prelude_accounts else {
+        panic!("Could not fetch prelude accounts");
+    };
+    let token_program = token_program.0;
+
+    let config = setup_config(context, fee_bps, 0);
+
+    let (maker, maker_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, &system_program.0);
+    let (taker, taker_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, &system_program.0);
+
+    let mut store = context.account_store.borrow_mut();
+    store.store_account(maker, maker_account);
+    store.store_account(taker, taker_account);
+
+    let mut fund = |owner: &Pubkey, amounts: &[u64]| -> Vec<(Pubkey, u64)> {
+        amounts
+            .iter()
+            .map(|amount| {
+                let (mint, mint_account) = keyed_account_for_mint_default(&maker, None, DECIMALS);
+                let (ata, ata_account) = keyed_account_for_associated_token_account(
+                    &mint,
+                    owner,
+                    *amount,
+                    Some(token_program),
+                );
+                store.store_account(mint, mint_account);
+                store.store_account(ata, ata_account);
+
+                (mint, *amount)
+            })
+            .collect()
+    };
+
+    let offered = fund(&maker, offered);
+    let requested = fund(&taker, requested);
+
+    let seed = random_u64();
+    let (basket, _) = Pubkey::find_program_address(
+        &[b"basket", maker.as_ref(), &seed.to_le_bytes()],
+        &PROGRAM_ID,
+    );
+
+    BasketFixture {
+        maker,
+        taker,
+        basket,
+        seed,
+        offered,
+        requested,
+        config,
+        system_program: system_program.0,
+        token_program,
+        associated_token_program: associated_token_program.0,
+    }
+}
+
+struct OrderFixture {
+    maker: Keypair,
+    taker: Pubkey,
+    a_mint: Pubkey,
+    b_mint: Pubkey,
+    amount: u64,
+    receive: u64,
+    seed: u64,
+    expiry: i64,
+    nonce: u64,
+    config: ConfigFixture,
+    system_program: Pubkey,
+    token_program: Pubkey,
+    associated_token_program: Pubkey,
+}
+
+/// Creates a maker whose `mint_a` ATA has approved the program's delegate for `amount`, and a
+/// taker holding `receive` of `mint_b`, along with a `Config` charging `fee_bps`.
+fn setup_order(
+    context: &MolluskContext<InMemoryAccountStore>,
+    prelude_accounts: &[(Pubkey, Account)],
+    amount: u64,
+    receive: u64,
+    expiry: i64,
+    fee_bps: u16,
+) -> OrderFixture {
+    let [token_program, associated_token_program, system_program] = prelude_accounts else {
+        panic!("Could not fetch prelude accounts");
+    };
+
+    let config = setup_config(context, fee_bps, 0);
+
+    let maker = Keypair::new();
+    let (taker, taker_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, &system_program.0);
+
+    let (a_mint, a_mint_account) = keyed_account_for_mint_default(&maker.pubkey(), None, DECIMALS);
+    let (b_mint, b_mint_account) = keyed_account_for_mint_default(&taker, None, DECIMALS);
+
+    let (maker_ata_a, maker_ata_a_account) = keyed_account_for_delegated_associated_token_account(
+        &a_mint,
+        &maker.pubkey(),
+        &delegate_pda(&PROGRAM_ID),
+        amount,
+    );
+    let (taker_ata_b, taker_ata_b_account) =
+        keyed_account_for_associated_token_account(&b_mint, &taker, receive, Some(token_program.0));
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(taker, taker_account);
+        store.store_account(a_mint, a_mint_account);
+        store.store_account(b_mint, b_mint_account);
+        store.store_account(maker_ata_a, maker_ata_a_account);
+        store.store_account(taker_ata_b, taker_ata_b_account);
+    }
+
+    OrderFixture {
+        maker,
+        taker,
+        a_mint,
+        b_mint,
+        amount,
+        receive,
+        seed: random_u64(),
+        expiry,
+        nonce: random_u64(),
+        config,
+        system_program: system_program.0,
+        token_program: token_program.0,
+        associated_token_program: associated_token_program.0,
+    }
+}
+
+struct TakeManyFixture {
+    taker: Pubkey,
+    a_mint: Pubkey,
+    b_mint: Pubkey,
+    offers: Vec<(Pubkey, Pubkey)>, // (maker, escrow)
+    config: ConfigFixture,
+    system_program: Pubkey,
+    token_program: Pubkey,
+    associated_token_program: Pubkey,
+}
+
+/// Makes one escrow per `(amount, receive)` offer on the same pair, each by a different maker,
+/// and creates a taker holding `funds` of `mint_b`, along with a `Config` charging `fee_bps`.
+fn setup_take_many(
+    context: &MolluskContext<InMemoryAccountStore>,
+    prelude_accounts: &[(Pubkey, Account)],
+    offers: &[(u64, u64)],
+    funds: u64,
+    fee_bps: u16,
+) -> TakeManyFixture {
+    let [token_program, associated_token_program, system_program] = prelude_accounts else {
+        panic!("Could not fetch prelude accounts");
+    };
+
+    let config = setup_config(context, fee_bps, 0);
+
+    let (taker, taker_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, &system_program.0);
+    let (a_mint, a_mint_account) = keyed_account_for_mint_default(&taker, None, DECIMALS);
+    let (b_mint, b_mint_account) = keyed_account_for_mint_default(&taker, None, DECIMALS);
+    let (taker_ata_b, taker_ata_b_account) =
+        keyed_account_for_associated_token_account(&b_mint, &taker, funds, Some(token_program.0));
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(taker, taker_account);
+        store.store_account(a_mint, a_mint_account);
+        store.store_account(b_mint, b_mint_account);
+        store.store_account(taker_ata_b, taker_ata_b_account);
+    }
+
+    let offers = offers
+        .iter()
+        .map(|(amount, receive)| {
+            let (maker, maker_account) = keyed_account_for_system_account_with_lamports(
+                10 * LAMPORTS_PER_SOL,
+                &system_program.0,
+            );
+            let (maker_ata_a, maker_ata_a_account) = keyed_account_for_associated_token_account(
+                &a_mint,
+                &maker,
+                *amount,
+                Some(token_program.0),
+            );
+
+            {
+                let mut store = context.account_store.borrow_mut();
+                store.store_account(maker, maker_account);
+                store.store_account(maker_ata_a, maker_ata_a_account);
+            }
+
+            let seed = random_u64();
+            let (escrow, _) = escrow_pda(&maker, seed);
+
+            make(
+                context,
+                &[
+                    maker,
+                    escrow,
+                    a_mint,
+                    b_mint,
+                    maker_ata_a,
+                    associated_token_address(&escrow, &a_mint),
+                    config.config,
+                    system_program.0,
+                    token_program.0,
+                    associated_token_program.0,
+                ],
+                &MakeArgs {
+                    amount: *amount,
+                    receive: *receive,
+                    seed,
+                    ..Default::default()
+                },
+                &[Check::success()],
+            );
+
+            (maker, escrow)
+        })
+        .collect();
+
+    TakeManyFixture {
+        taker,
+        a_mint,
+        b_mint,
+        offers,
+        config,
+        system_program: system_program.0,
+        token_program: token_program.0,
+        associated_token_program: associated_token_program.0,
+    }
+}
+
+struct CounterFixture {
+    proposer: Pubkey,
+    counter: Pubkey,
+    make: [Pubkey; 10],
+    config: ConfigFixture,
+}
+
+/// Creates a funded proposer holding `funds` of `mint_b` for the escrow made with `make_accounts`.
+fn setup_counter(
+    context: &MolluskContext<InMemoryAccountStore>,
+    make_accounts: &[Pubkey; 10],
+    config: ConfigFixture,
+    funds: u64,
+) -> CounterFixture {
+    let [_, escrow, _, b_mint, _, _, _, system_program, token_program, _] = make_accounts;
+
+    let (proposer, proposer_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, system_program);
+    let (proposer_ata_b, proposer_ata_b_account) =
+        keyed_account_for_associated_token_account(b_mint, &proposer, funds, Some(*token_program));
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(proposer, proposer_account);
+        store.store_account(proposer_ata_b, proposer_ata_b_account);
+    }
+
+    CounterFixture {
+        proposer,
+        counter: counter_pda(escrow, &proposer),
+        make: *make_accounts,
+        config,
+    }
+}
+
+/// Milestone, vesting and stream escrows all lock a funder's tokens at
+/// `[prefix, funder, seed]` for a recipient, `authority` and `arbiter` only matter to milestones.
+struct LockFixture {
+    funder: Pubkey,
+    recipient: Pubkey,
+    authority: Pubkey,
+    arbiter: Pubkey,
+    escrow: Pubkey,
+    mint: Pubkey,
+    seed: u64,
+    config: ConfigFixture,
+    system_program: Pubkey,
+    token_program: Pubkey,
+    associated_token_program: Pubkey,
+}
+
+/// Creates a funder holding `funds` of a fresh mint, and a funded recipient, release authority
+/// and arbiter, along with a fee-less `Config`. The escrow is derived from `prefix`.
+fn setup_lock(
+    context: &MolluskContext<InMemoryAccountStore>,
+    prelude_accounts: &[(Pubkey, Account)],
+    prefix: &[u8],
+    funds: u64,
+) -> LockFixture {
+    let [token_program, associated_token_program, system_program] = prelude_accounts else {
+        panic!("Could not fetch prelude accounts");
+    };
+
+    let config = setup_config(context, 0, 0);
+
+    let [funder, recipient, authority, arbiter] = [(); 4].map(|_| {
+        let (pubkey, account) = keyed_account_for_system_account_with_lamports(
+            10 * LAMPORTS_PER_SOL,
+            &system_program.0,
+        );
+        context
+            .account_store
+            .borrow_mut()
+            .store_account(pubkey, account);
+
+        pubkey
+    });
+
+    let (mint, mint_account) = keyed_account_for_mint_default(&funder, None, DECIMALS);
+    let (funder_ata, funder_ata_account) =
+        keyed_account_for_associated_token_account(&mint, &funder, funds, Some(token_program.0));
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(mint, mint_account);
+        store.store_account(funder_ata, funder_ata_account);
+    }
+
+    let seed = random_u64();
+    let (escrow, _) =
+        Pubkey::find_program_address(&[prefix, funder.as_ref(), &seed.to_le_bytes()], &PROGRAM_ID);
+
+    LockFixture {
+        funder,
+        recipient,
+        authority,
+        arbiter,
+        escrow,
+        mint,
+        seed,
+        config,
+        system_program: system_program.0,
+        token_program: token_program.0,
+        associated_token_program: associated_token_program.0,
+    }
+}
+
+/// Creates a funded taker holding `amount` of `mint_b` and stores it. Returns the accounts in
+/// `take` order.
+fn setup_take(
+    context: &MolluskContext<InMemoryAccountStore>,
+    make_accounts: &[Pubkey],
+    config: &ConfigFixture,
+    amount: u64,
+) -> [Pubkey; 15] {
+    let [maker, escrow, a_mint, b_mint, _maker_ata_a, escrow_ata, _config, system_program, token_program, associated_token_program] =
+        make_accounts
+    else {
+        panic!("Could not unpack make accounts")
+    };
+
+    let (taker, taker_account) =
+        keyed_account_for_system_account_with_lamports(10 * LAMPORTS_PER_SOL, system_program);
+
+    let (taker_ata_b, taker_ata_b_account) =
+        keyed_account_for_associated_token_account(b_mint, &taker, amount, Some(*token_program));
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(taker, taker_account);
+        store.store_account(taker_ata_b, taker_ata_b_account);
+    }
+
+    [
+        taker,
+        *maker,
+        *escrow,
+        *a_mint,
+        *b_mint,
+        *escrow_ata,
+        associated_token_address(&taker, a_mint),
+        taker_ata_b,
+        associated_token_address(maker, b_mint),
+        config.config,
+        config.fee_recipient,
+        associated_token_address(&config.fee_recipient, b_mint),
+        *system_program,
+        *token_program,
+        *associated_token_program,
+    ]
+}
+
+#[test]
+fn test_make() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+
+    let maker_ata_pubkey = accounts[4].0;
+    let escrow_ata_pubkey = accounts[5].0;
+
+    println!("Associated addr: {}", &escrow_ata_pubkey);
+
     let amount = starting_tokens_amount / 10;
     let receive = 1000 * 10u32.pow(DECIMALS as u32);
 
@@ -133,7 +695,7 @@ fn test_make() {
         Check::account(&maker_ata_pubkey)
             .data_slice(64, &maker_amount_bytes)
             .build(),
-        Check::account(&escrow_ata_pubkey.0)
+        Check::account(&escrow_ata_pubkey)
             .data_slice(64, &escrow_amount_bytes)
             .build(),
     ];
@@ -142,60 +704,4853 @@ fn test_make() {
     let _make_result = make(
         &context,
         account_pubkeys,
-        amount,
-        receive as u64,
-        seed,
+        &MakeArgs {
+            amount,
+            receive: receive as u64,
+            seed,
+            ..Default::default()
+        },
         make_checks,
     );
 }
 
-fn keyed_account_for_system_account_with_lamports(
-    lamports: u64,
-    owner: &Pubkey,
-) -> (Pubkey, Account) {
-    let payer = Pubkey::new_unique();
-    let payer_account = Account::new(lamports, 0, owner);
+#[test]
+fn test_make_with_past_expiry_fails() {
+    let (mut context, prelude_accounts) = prelude();
+    context.mollusk.sysvars.clock.unix_timestamp = 1_000;
 
-    (payer, payer_account)
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+
+    let account_pubkeys = &accounts.map(|a| a.0);
+    make(
+        &context,
+        account_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount,
+            receive: 1,
+            seed,
+            expiry: Some(1_000),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::Custom(INVALID_EXPIRY))],
+    );
 }
 
-fn make(
-    context: &MolluskContext<InMemoryAccountStore>,
-    accounts: &[Pubkey],
-    amount: u64,
-    receive: u64,
-    seed: u64,
-    checks: &[Check],
-) -> ContextResult {
-    let [maker, escrow, a_mint, b_mint, maker_ata, escrow_ata, system_program, token_program, associated_token_program] =
-        accounts
-    else {
-        panic!("Could not unpack accounts in make")
-    };
+#[test]
+fn test_take() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
 
-    let mut instruction_data = vec![];
-    instruction_data.extend_from_slice(&[0]); // make instruction DISCRIMINATOR
-    instruction_data.extend_from_slice(amount.to_le_bytes().as_ref());
-    instruction_data.extend_from_slice(receive.to_le_bytes().as_ref());
-    instruction_data.extend_from_slice(seed.to_le_bytes().as_ref());
+    let amount = starting_tokens_amount / 10;
+    let receive = starting_tokens_amount / 4;
 
-    let instruction = Instruction::new_with_bytes(
-        PROGRAM_ID.into(),
-        instruction_data.as_slice(),
-        vec![
-            AccountMeta::new(maker.into(), true),
-            AccountMeta::new(escrow.into(), false),
-            AccountMeta::new(a_mint.into(), false),
-            AccountMeta::new(b_mint.into(), false),
-            AccountMeta::new(maker_ata.into(), false),
-            AccountMeta::new(escrow_ata.into(), false),
-            AccountMeta::new(system_program.into(), false),
-            AccountMeta::new(token_program.into(), false),
-            AccountMeta::new(associated_token_program.into(), false),
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [_, _, escrow, _, _, escrow_ata, taker_ata_a, taker_ata_b, maker_ata_b, ..] = take_pubkeys;
+
+    let amount_bytes = amount.to_le_bytes();
+    let receive_bytes = receive.to_le_bytes();
+    let zero_bytes = 0u64.to_le_bytes();
+
+    // Checks:
+    //  - Taker receives the whole vault in `mint_a` and pays `receive` of `mint_b` to the maker.
+    //  - Escrow and vault are closed.
+    take(
+        &context,
+        &take_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&taker_ata_a)
+                .data_slice(64, &amount_bytes)
+                .build(),
+            Check::account(&taker_ata_b)
+                .data_slice(64, &zero_bytes)
+                .build(),
+            Check::account(&maker_ata_b)
+                .data_slice(64, &receive_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&escrow_ata).closed().build(),
         ],
     );
+}
+
+#[test]
+fn test_take_slippage_limits() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let amount = starting_tokens_amount / 10;
+    let receive = starting_tokens_amount / 4;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [_, _, escrow, ..] = take_pubkeys;
+
+    // Limits are either both passed or both omitted
+    let mut instruction = Instruction::new_with_bytes(PROGRAM_ID, &[1], vec![]);
+    instruction.data.extend_from_slice(&receive.to_le_bytes());
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    take_with_limits(
+        &context,
+        &take_pubkeys,
+        Some((receive - 1, 0)),
+        &[Check::err(ProgramError::Custom(MAX_PAY_EXCEEDED))],
+    );
+
+    take_with_limits(
+        &context,
+        &take_pubkeys,
+        Some((u64::MAX, amount + 1)),
+        &[Check::err(ProgramError::Custom(MIN_RECEIVE_NOT_MET))],
+    );
+
+    take_with_limits(
+        &context,
+        &take_pubkeys,
+        Some((receive, amount)),
+        &[Check::success(), Check::account(&escrow).closed().build()],
+    );
+}
+
+#[test]
+fn test_take_expired_fails() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let expiry = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount,
+            receive: 1,
+            seed,
+            expiry: Some(expiry),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, 1);
+
+    context.mollusk.sysvars.clock.unix_timestamp = expiry + 1;
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::err(ProgramError::Custom(OFFER_EXPIRED))],
+    );
+}
+
+#[test]
+fn test_take_private() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+    let designated_take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let other_take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount,
+            receive,
+            seed,
+            taker: Some(designated_take_pubkeys[0]),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    take(
+        &context,
+        &other_take_pubkeys,
+        &[Check::err(ProgramError::Custom(INVALID_TAKER))],
+    );
+
+    let escrow = designated_take_pubkeys[2];
+    take(
+        &context,
+        &designated_take_pubkeys,
+        &[Check::success(), Check::account(&escrow).closed().build()],
+    );
+}
+
+#[test]
+fn test_close_expired() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, _, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let expiry = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive: 1,
+            seed,
+            expiry: Some(expiry),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    // Anyone may close an expired escrow, the funds always go back to the maker.
+    let (cranker, cranker_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &system_program);
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(cranker, cranker_account);
+
+    let close_pubkeys = [
+        cranker,
+        maker,
+        escrow,
+        a_mint,
+        escrow_ata,
+        maker_ata_a,
+        system_program,
+        token_program,
+        associated_token_program,
+    ];
+
+    // Not expired yet
+    close_expired(
+        &context,
+        &close_pubkeys,
+        &[Check::err(ProgramError::Custom(OFFER_NOT_EXPIRED))],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = expiry + 1;
+
+    let starting_amount_bytes = starting_tokens_amount.to_le_bytes();
+    close_expired(
+        &context,
+        &close_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &starting_amount_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&escrow_ata).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_with_fee() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 100, 0); // 1%
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+    let fee = receive / 100;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 10,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [_, _, _, _, _, _, _, taker_ata_b, maker_ata_b, _, _, fee_recipient_ata_b, ..] =
+        take_pubkeys;
+
+    let maker_amount_bytes = (receive - fee).to_le_bytes();
+    let fee_bytes = fee.to_le_bytes();
+    let zero_bytes = 0u64.to_le_bytes();
+
+    // Checks:
+    //  - Taker pays exactly `receive`.
+    //  - The fee is taken out of the maker's side and lands in the fee recipient's ATA.
+    take(
+        &context,
+        &take_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&taker_ata_b)
+                .data_slice(64, &zero_bytes)
+                .build(),
+            Check::account(&maker_ata_b)
+                .data_slice(64, &maker_amount_bytes)
+                .build(),
+            Check::account(&fee_recipient_ata_b)
+                .data_slice(64, &fee_bytes)
+                .build(),
+        ],
+    );
+}
+
+/// Funds the config's admin and has it register `referrer` for `b_mint`, returning the
+/// referrer's `mint_b` ATA (created empty unless it exists) and referral PDA.
+fn setup_referral(
+    context: &MolluskContext<InMemoryAccountStore>,
+    config: &ConfigFixture,
+    referrer: &Pubkey,
+    b_mint: &Pubkey,
+    token_program: &Pubkey,
+) -> (Pubkey, Pubkey) {
+    let (_, admin_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (referrer_ata_b, referrer_ata_b_account) =
+        keyed_account_for_associated_token_account(b_mint, referrer, 0, Some(*token_program));
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(config.admin, admin_account);
+        if store.get_account(&referrer_ata_b).is_none() {
+            store.store_account(referrer_ata_b, referrer_ata_b_account);
+        }
+    }
+
+    let (referral, _) = referral_pda(&PROGRAM_ID, referrer, b_mint);
+    let (system_program, _) = keyed_account_for_system_program();
+
+    register_referral(
+        context,
+        &[
+            config.admin,
+            config.config,
+            *referrer,
+            *b_mint,
+            referral,
+            system_program,
+        ],
+        &[Check::success()],
+    );
+
+    (referrer_ata_b, referral)
+}
+
+#[test]
+fn test_take_with_referral() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 100, 5_000); // 1%, half of it to the referrer
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+    let fee = receive / 100;
+    let referral_fee = fee / 2;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 10,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [_, _, _, _, b_mint, _, _, _, maker_ata_b, _, _, fee_recipient_ata_b, _, token_program, _] =
+        take_pubkeys;
+
+    let referrer = Pubkey::new_unique();
+    let (referrer_ata_b, referral) =
+        setup_referral(&context, &config, &referrer, &b_mint, &token_program);
+
+    let mut routed_pubkeys = take_pubkeys.to_vec();
+    routed_pubkeys.extend([referrer_ata_b, referral]);
+
+    let maker_amount_bytes = (receive - fee).to_le_bytes();
+    let protocol_fee_bytes = (fee - referral_fee).to_le_bytes();
+    let referral_fee_bytes = referral_fee.to_le_bytes();
+    let receive_bytes = receive.to_le_bytes();
+
+    // Checks:
+    //  - The maker still receives `receive - fee`, the fee is split between the protocol and
+    //    the referrer.
+    //  - The referral PDA tracks the routed volume and earned fees.
+    take(
+        &context,
+        &routed_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&maker_ata_b)
+                .data_slice(64, &maker_amount_bytes)
+                .build(),
+            Check::account(&fee_recipient_ata_b)
+                .data_slice(64, &protocol_fee_bytes)
+                .build(),
+            Check::account(&referrer_ata_b)
+                .data_slice(64, &referral_fee_bytes)
+                .build(),
+            Check::account(&referral)
+                .owner(&PROGRAM_ID)
+                .space(REFERRAL_LEN)
+                .data_slice(72, &receive_bytes)
+                .build(),
+            Check::account(&referral)
+                .data_slice(80, &referral_fee_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_with_unregistered_referral() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 100, 5_000);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 10,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [_, _, _, _, b_mint, _, _, _, _, _, _, _, _, token_program, _] = take_pubkeys;
+
+    // Any `mint_b` holder can name themselves as referrer, but without a registration there is
+    // no referral account to pay them through.
+    let referrer = Pubkey::new_unique();
+    let (referrer_ata_b, referrer_ata_b_account) =
+        keyed_account_for_associated_token_account(&b_mint, &referrer, 0, Some(token_program));
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(referrer_ata_b, referrer_ata_b_account);
+    let (referral, _) = referral_pda(&PROGRAM_ID, &referrer, &b_mint);
+
+    let mut routed_pubkeys = take_pubkeys.to_vec();
+    routed_pubkeys.extend([referrer_ata_b, referral]);
+
+    take(
+        &context,
+        &routed_pubkeys,
+        &[Check::err(ProgramError::InvalidAccountOwner)],
+    );
+
+    // Only the config's admin can register referrers
+    let (impostor, impostor_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(impostor, impostor_account);
+    let (system_program, _) = keyed_account_for_system_program();
+
+    register_referral(
+        &context,
+        &[
+            impostor,
+            config.config,
+            referrer,
+            b_mint,
+            referral,
+            system_program,
+        ],
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+}
+
+#[test]
+fn test_take_with_self_referral() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_config(&context, 100, 5_000);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 10,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    let [taker, maker, _, _, b_mint, _, _, _, _, _, _, _, _, token_program, _] = take_pubkeys;
+
+    // Even registered, neither party of the fill can take the referral share
+    for party in [taker, maker] {
+        let (referrer_ata_b, referral) =
+            setup_referral(&context, &config, &party, &b_mint, &token_program);
+
+        let mut routed_pubkeys = take_pubkeys.to_vec();
+        routed_pubkeys.extend([referrer_ata_b, referral]);
+
+        take(
+            &context,
+            &routed_pubkeys,
+            &[Check::err(ProgramError::Custom(INVALID_REFERRER))],
+        );
+    }
+}
+
+#[test]
+fn test_init_config() {
+    let (context, _) = prelude();
+
+    let (authority, authority_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (program_data, program_data_account) =
+        keyed_account_for_program_data(&PROGRAM_ID, &authority);
+    let (impostor, impostor_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (config, _) = config_pda(&PROGRAM_ID);
+    let fee_recipient = Pubkey::new_unique();
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(authority, authority_account);
+        store.store_account(program_data, program_data_account);
+        store.store_account(impostor, impostor_account);
+    }
+
+    // Only the upgrade authority may initialize the config
+    init_config(
+        &context,
+        &[impostor, config, program_data],
+        100,
+        0,
+        &fee_recipient,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    init_config(
+        &context,
+        &[authority, config, program_data],
+        10_001, // above 100%
+        0,
+        &fee_recipient,
+        &[Check::err(ProgramError::Custom(INVALID_FEE_BPS))],
+    );
+
+    init_config(
+        &context,
+        &[authority, config, program_data],
+        100,
+        0,
+        &fee_recipient,
+        &[
+            Check::success(),
+            Check::account(&config)
+                .owner(&PROGRAM_ID)
+                .data_slice(0, &account_header(CONFIG_DISCRIMINATOR))
+                .build(),
+            Check::account(&config)
+                .data_slice(8, authority.as_ref())
+                .build(),
+            Check::account(&config)
+                .data_slice(40, fee_recipient.as_ref())
+                .build(),
+            Check::account(&config)
+                .data_slice(72, &100u16.to_le_bytes())
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_update_config() {
+    let (context, _) = prelude();
+    let config = setup_config(&context, 0, 0);
+
+    let (impostor, impostor_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (_, admin_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let new_admin = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(config.admin, admin_account);
+        store.store_account(impostor, impostor_account);
+    }
+
+    update_config(
+        &context,
+        &[impostor, config.config],
+        250,
+        0,
+        &fee_recipient,
+        &new_admin,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    update_config(
+        &context,
+        &[config.admin, config.config],
+        250,
+        2_000,
+        &fee_recipient,
+        &new_admin,
+        &[
+            Check::success(),
+            Check::account(&config.config)
+                .data_slice(8, new_admin.as_ref())
+                .build(),
+            Check::account(&config.config)
+                .data_slice(40, fee_recipient.as_ref())
+                .build(),
+            Check::account(&config.config)
+                .data_slice(72, &250u16.to_le_bytes())
+                .build(),
+            Check::account(&config.config)
+                .data_slice(74, &2_000u16.to_le_bytes())
+                .build(),
+        ],
+    );
+
+    // The previous admin lost its rights
+    update_config(
+        &context,
+        &[config.admin, config.config],
+        0,
+        0,
+        &fee_recipient,
+        &config.admin,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+}
+
+#[test]
+fn test_make_nft() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, b_mint, _, escrow_ata, ..] = *make_pubkeys;
+
+    // Neither mint is an NFT yet
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: 1,
+            receive: 1,
+            seed,
+            mode: Some(NFT_TOKEN_A | NFT_TOKEN_B),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::Custom(NOT_AN_NFT))],
+    );
+
+    let (_, nft_account) = keyed_account_for_nft_mint(&maker, Some(a_mint));
+    let (_, nft_b_account) = keyed_account_for_nft_mint(&maker, Some(b_mint));
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(a_mint, nft_account);
+        store.store_account(b_mint, nft_b_account);
+    }
+
+    // NFTs are only traded whole
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: 2,
+            receive: 1,
+            seed,
+            mode: Some(NFT_TOKEN_A),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: 1,
+            receive: 2,
+            seed,
+            mode: Some(NFT_TOKEN_A | NFT_TOKEN_B),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    let one_bytes = 1u64.to_le_bytes();
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: 1,
+            receive: 1,
+            seed,
+            mode: Some(NFT_TOKEN_A | NFT_TOKEN_B),
+            ..Default::default()
+        },
+        &[
+            Check::success(),
+            Check::account(&escrow).owner(&PROGRAM_ID).build(),
+            Check::account(&escrow_ata)
+                .data_slice(64, &one_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_nft_with_collection() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, ..] = *make_pubkeys;
+
+    let (_, nft_account) = keyed_account_for_nft_mint(&maker, Some(a_mint));
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(a_mint, nft_account);
+
+    let collection = Pubkey::new_unique();
+    let mut nft_pubkeys = make_pubkeys.to_vec();
+    nft_pubkeys.push(metadata_pda(&a_mint));
+
+    let args = MakeArgs {
+        amount: 1,
+        receive: 1,
+        seed,
+        mode: Some(NFT_TOKEN_A),
+        collection: Some(collection),
+        ..Default::default()
+    };
+
+    // Unverified membership, verified membership of another collection, no collection
+    for metadata in [
+        keyed_account_for_metadata(&a_mint, Some((collection, false))),
+        keyed_account_for_metadata(&a_mint, Some((Pubkey::new_unique(), true))),
+        keyed_account_for_metadata(&a_mint, None),
+    ] {
+        context
+            .account_store
+            .borrow_mut()
+            .store_account(metadata.0, metadata.1);
+
+        make(
+            &context,
+            &nft_pubkeys,
+            &args,
+            &[Check::err(ProgramError::Custom(INVALID_COLLECTION))],
+        );
+    }
+
+    // The metadata account is required to check the collection
+    make(
+        &context,
+        make_pubkeys,
+        &args,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+
+    let (metadata, metadata_account) =
+        keyed_account_for_metadata(&a_mint, Some((collection, true)));
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(metadata, metadata_account);
+
+    make(
+        &context,
+        &nft_pubkeys,
+        &args,
+        &[
+            Check::success(),
+            Check::account(&escrow).owner(&PROGRAM_ID).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_refund() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let refund_pubkeys = refund_accounts(make_pubkeys);
+    let [_, escrow, _, escrow_ata, maker_ata_a, ..] = refund_pubkeys;
+
+    // Only the maker may refund
+    let (stranger, stranger_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(stranger, stranger_account);
+    let mut stranger_pubkeys = refund_pubkeys;
+    stranger_pubkeys[0] = stranger;
+    refund(
+        &context,
+        &stranger_pubkeys,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    let starting_amount_bytes = starting_tokens_amount.to_le_bytes();
+    refund(
+        &context,
+        &refund_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &starting_amount_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&escrow_ata).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_update() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, config, _, token_program, _] =
+        *make_pubkeys;
+
+    let amount = starting_tokens_amount / 2;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let update_pubkeys = [
+        maker,
+        escrow,
+        a_mint,
+        maker_ata_a,
+        escrow_ata,
+        config,
+        token_program,
+    ];
+
+    // Re-price and top up the vault with the rest of the maker's balance
+    let new_receive_bytes = 42u64.to_le_bytes();
+    let starting_amount_bytes = starting_tokens_amount.to_le_bytes();
+    let zero_bytes = 0u64.to_le_bytes();
+    update(
+        &context,
+        &update_pubkeys,
+        [42, starting_tokens_amount - amount, 0],
+        &[
+            Check::success(),
+            Check::account(&escrow)
+                .data_slice(ESCROW_RECEIVE_OFFSET, &new_receive_bytes)
+                .build(),
+            Check::account(&escrow_ata)
+                .data_slice(64, &starting_amount_bytes)
+                .build(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &zero_bytes)
+                .build(),
+        ],
+    );
+
+    // The vault can't be emptied, that's what `refund` is for
+    update(
+        &context,
+        &update_pubkeys,
+        [42, 0, starting_tokens_amount],
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+
+    let withdraw = starting_tokens_amount / 4;
+    let vault_amount_bytes = (starting_tokens_amount - withdraw).to_le_bytes();
+    let withdraw_bytes = withdraw.to_le_bytes();
+    update(
+        &context,
+        &update_pubkeys,
+        [42, 0, withdraw],
+        &[
+            Check::success(),
+            Check::account(&escrow_ata)
+                .data_slice(64, &vault_amount_bytes)
+                .build(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &withdraw_bytes)
+                .build(),
+        ],
+    );
+
+    update(
+        &context,
+        &update_pubkeys,
+        [42, 1, 1],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    // Amending is blocked while paused
+    setup_paused_config(&context);
+    update(
+        &context,
+        &update_pubkeys,
+        [1, 0, 0],
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+#[test]
+fn test_update_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, _, _, token_program, _] = *make_pubkeys;
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let config = setup_paused_config(&context);
+    let update_pubkeys = [
+        maker,
+        escrow,
+        a_mint,
+        maker_ata_a,
+        escrow_ata,
+        config.config,
+        token_program,
+    ];
+
+    update(
+        &context,
+        &update_pubkeys,
+        [42, 0, 0],
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        false,
+        &[Check::success()],
+    );
+
+    let new_receive_bytes = 42u64.to_le_bytes();
+    update(
+        &context,
+        &update_pubkeys,
+        [42, 0, 0],
+        &[
+            Check::success(),
+            Check::account(&escrow)
+                .data_slice(ESCROW_RECEIVE_OFFSET, &new_receive_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_migrate_legacy_escrow() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, b_mint, maker_ata_a, escrow_ata, config, system_program, token_program, _] =
+        *make_pubkeys;
+
+    let receive = 1_000u64;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount,
+            receive,
+            seed,
+            taker: Some(Pubkey::new_unique()),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    // Rewrite the escrow as the deployed program laid it out, before the account header and
+    // the optional terms
+    {
+        let (_, bump) = escrow_pda(&maker, seed);
+
+        let mut data = Vec::with_capacity(LEGACY_ESCROW_LEN);
+        data.extend_from_slice(maker.as_ref());
+        data.extend_from_slice(a_mint.as_ref());
+        data.extend_from_slice(b_mint.as_ref());
+        data.extend_from_slice(&receive.to_le_bytes());
+        data.extend_from_slice(&seed.to_le_bytes());
+        data.push(bump);
+
+        let mut store = context.account_store.borrow_mut();
+        let mut account = store.get_account(&escrow).unwrap();
+        account.data = data;
+        account.lamports = context
+            .mollusk
+            .sysvars
+            .rent
+            .minimum_balance(LEGACY_ESCROW_LEN);
+        store.store_account(escrow, account);
+    }
+
+    let update_accounts = [
+        maker,
+        escrow,
+        a_mint,
+        maker_ata_a,
+        escrow_ata,
+        config,
+        token_program,
+    ];
+
+    // Legacy escrows have to be migrated before use
+    update(
+        &context,
+        &update_accounts,
+        [42, 0, 0],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[28], // migrate instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(maker, true),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    let receive_bytes = receive.to_le_bytes();
+    let seed_bytes = seed.to_le_bytes();
+
+    // Checks:
+    //  - The escrow is resized, rent-exempt and gets the current header.
+    //  - The legacy fields keep their values, the new ones their defaults: the designated
+    //    taker the escrow was made with above is gone, as legacy escrows had none.
+    context.process_and_validate_instruction(
+        &instruction,
+        &[
+            Check::success(),
+            Check::account(&escrow)
+                .space(ACCOUNT_HEADER_LEN + ESCROW_BODY_LEN)
+                .lamports(
+                    context
+                        .mollusk
+                        .sysvars
+                        .rent
+                        .minimum_balance(ACCOUNT_HEADER_LEN + ESCROW_BODY_LEN),
+                )
+                .build(),
+            Check::account(&escrow)
+                .data_slice(0, &account_header(ESCROW_DISCRIMINATOR))
+                .build(),
+            Check::account(&escrow)
+                .data_slice(ACCOUNT_HEADER_LEN, maker.as_ref())
+                .build(),
+            Check::account(&escrow)
+                .data_slice(ACCOUNT_HEADER_LEN + 32 * 2, b_mint.as_ref())
+                .build(),
+            Check::account(&escrow)
+                .data_slice(ACCOUNT_HEADER_LEN + 32 * 3, &[0; 32])
+                .build(),
+            Check::account(&escrow)
+                .data_slice(ESCROW_RECEIVE_OFFSET, &receive_bytes)
+                .build(),
+            Check::account(&escrow)
+                .data_slice(ESCROW_RECEIVE_OFFSET + 8, &seed_bytes)
+                .build(),
+        ],
+    );
+
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(ALREADY_MIGRATED))],
+    );
+
+    // Once migrated, the escrow works as any other
+    let new_receive_bytes = 42u64.to_le_bytes();
+    update(
+        &context,
+        &update_accounts,
+        [42, 0, 0],
+        &[
+            Check::success(),
+            Check::account(&escrow)
+                .data_slice(ESCROW_RECEIVE_OFFSET, &new_receive_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_set_paused() {
+    let (context, _) = prelude();
+    let config = setup_config(&context, 0, 0);
+
+    let (impostor, impostor_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+    let (_, admin_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &Pubkey::default());
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        store.store_account(config.admin, admin_account);
+        store.store_account(impostor, impostor_account);
+    }
+
+    set_paused(
+        &context,
+        &[impostor, config.config],
+        true,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        true,
+        &[
+            Check::success(),
+            Check::account(&config.config).data_slice(76, &[1]).build(),
+        ],
+    );
+
+    // The admin keeps full control of the config while paused
+    update_config(
+        &context,
+        &[config.admin, config.config],
+        100,
+        0,
+        &config.fee_recipient,
+        &config.admin,
+        &[
+            Check::success(),
+            Check::account(&config.config).data_slice(76, &[1]).build(),
+        ],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        false,
+        &[
+            Check::success(),
+            Check::account(&config.config).data_slice(76, &[0]).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let config = setup_paused_config(&context);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let args = MakeArgs {
+        amount: starting_tokens_amount / 2,
+        receive: 1,
+        seed,
+        ..Default::default()
+    };
+
+    make(
+        &context,
+        make_pubkeys,
+        &args,
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        false,
+        &[Check::success()],
+    );
+
+    make(&context, make_pubkeys, &args, &[Check::success()]);
+}
+
+#[test]
+fn test_take_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    let receive = starting_tokens_amount / 4;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let config = setup_paused_config(&context);
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        false,
+        &[Check::success()],
+    );
+
+    let escrow = take_pubkeys[2];
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::success(), Check::account(&escrow).closed().build()],
+    );
+}
+
+#[test]
+fn test_refund_while_paused() {
+    let (context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+
+    let refund_pubkeys = refund_accounts(make_pubkeys);
+    let [_, escrow, _, _, maker_ata_a, ..] = refund_pubkeys;
+
+    let starting_amount_bytes = starting_tokens_amount.to_le_bytes();
+    refund(
+        &context,
+        &refund_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &starting_amount_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_close_expired_while_paused() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let starting_tokens_amount: u64 = (100 * 10u32.pow(DECIMALS as u32)) as u64;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, starting_tokens_amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, _, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let expiry = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: starting_tokens_amount / 2,
+            receive: 1,
+            seed,
+            expiry: Some(expiry),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+    context.mollusk.sysvars.clock.unix_timestamp = expiry + 1;
+
+    // The maker pays for nothing here, its ATA already exists
+    close_expired(
+        &context,
+        &[
+            maker,
+            maker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            maker_ata_a,
+            system_program,
+            token_program,
+            associated_token_program,
+        ],
+        &[Check::success(), Check::account(&escrow).closed().build()],
+    );
+}
+
+#[test]
+fn test_make_and_take_basket() {
+    let (context, prelude_accounts) = prelude();
+
+    let basket = setup_basket(
+        &context,
+        &prelude_accounts,
+        &[1_000, 2_000],
+        &[3_000, 4_000],
+        100, // 1%
+    );
+
+    let legs = basket
+        .offered
+        .iter()
+        .chain(&basket.requested)
+        .map(|(mint, amount)| [mint.as_ref(), &amount.to_le_bytes()].concat())
+        .collect::<Vec<_>>();
+    let vaults = basket
+        .offered
+        .iter()
+        .map(|(mint, _)| associated_token_address(&basket.basket, mint))
+        .collect::<Vec<_>>();
+    let offered_amounts = basket
+        .offered
+        .iter()
+        .map(|(_, amount)| amount.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    // Checks:
+    //  - The basket records every leg, offered first.
+    //  - Each offered leg sits in its own vault.
+    let mut checks = vec![Check::success()];
+    for (i, leg) in legs.iter().enumerate() {
+        checks.push(
+            Check::account(&basket.basket)
+                .data_slice(BASKET_HEADER_LEN + i * BASKET_LEG_LEN, leg)
+                .build(),
+        );
+    }
+    for (vault, amount) in vaults.iter().zip(&offered_amounts) {
+        checks.push(Check::account(vault).data_slice(64, amount).build());
+    }
+
+    context.process_and_validate_instruction(&make_basket_instruction(&basket, 0), &checks);
+
+    let taker_atas = basket
+        .offered
+        .iter()
+        .map(|(mint, _)| associated_token_address(&basket.taker, mint))
+        .collect::<Vec<_>>();
+    let maker_atas = basket
+        .requested
+        .iter()
+        .map(|(mint, _)| associated_token_address(&basket.maker, mint))
+        .collect::<Vec<_>>();
+    let fee_recipient_atas = basket
+        .requested
+        .iter()
+        .map(|(mint, _)| associated_token_address(&basket.config.fee_recipient, mint))
+        .collect::<Vec<_>>();
+    let maker_amounts = basket
+        .requested
+        .iter()
+        .map(|(_, amount)| (amount - amount / 100).to_le_bytes())
+        .collect::<Vec<_>>();
+    let fee_amounts = basket
+        .requested
+        .iter()
+        .map(|(_, amount)| (amount / 100).to_le_bytes())
+        .collect::<Vec<_>>();
+
+    // Checks:
+    //  - Every offered leg goes to the taker and every vault is closed.
+    //  - Every requested leg goes to the maker, minus the protocol fee.
+    let mut checks = vec![
+        Check::success(),
+        Check::account(&basket.basket).closed().build(),
+    ];
+    for ((taker_ata, vault), amount) in taker_atas.iter().zip(&vaults).zip(&offered_amounts) {
+        checks.push(Check::account(taker_ata).data_slice(64, amount).build());
+        checks.push(Check::account(vault).closed().build());
+    }
+    for (maker_ata, amount) in maker_atas.iter().zip(&maker_amounts) {
+        checks.push(Check::account(maker_ata).data_slice(64, amount).build());
+    }
+    for (fee_recipient_ata, amount) in fee_recipient_atas.iter().zip(&fee_amounts) {
+        checks.push(
+            Check::account(fee_recipient_ata)
+                .data_slice(64, amount)
+                .build(),
+        );
+    }
+
+    context.process_and_validate_instruction(&take_basket_instruction(&basket), &checks);
+}
+
+#[test]
+fn test_take_basket_invalid_legs_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let basket = setup_basket(&context, &prelude_accounts, &[1_000, 2_000], &[3_000], 0);
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::success()],
+    );
+
+    // A missing leg must fail the whole settlement
+    let mut instruction = take_basket_instruction(&basket);
+    instruction
+        .accounts
+        .truncate(instruction.accounts.len() - 4);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(INVALID_LEGS))],
+    );
+
+    // Legs out of order
+    let mut instruction = take_basket_instruction(&basket);
+    let (offered, _) = instruction.accounts[8..].split_at_mut(6);
+    let (first, second) = offered.split_at_mut(3);
+    first.swap_with_slice(second);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(INVALID_LEGS))],
+    );
+
+    context.process_and_validate_instruction(
+        &take_basket_instruction(&basket),
+        &[
+            Check::success(),
+            Check::account(&basket.basket).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_basket_invalid_legs_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let basket = setup_basket(&context, &prelude_accounts, &[1; 5], &[1], 0);
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::err(ProgramError::Custom(INVALID_LEGS))],
+    );
+
+    // The same mint offered twice
+    let mut basket = setup_basket(&context, &prelude_accounts, &[1, 1], &[1], 0);
+    basket.offered[1] = basket.offered[0];
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::err(ProgramError::Custom(INVALID_LEGS))],
+    );
+}
+
+#[test]
+fn test_refund_basket() {
+    let (context, prelude_accounts) = prelude();
+
+    let basket = setup_basket(&context, &prelude_accounts, &[1_000, 2_000], &[3_000], 0);
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::success()],
+    );
+
+    // Refunds keep working while paused
+    setup_paused_config(&context);
+
+    let maker_atas = basket
+        .offered
+        .iter()
+        .map(|(mint, _)| associated_token_address(&basket.maker, mint))
+        .collect::<Vec<_>>();
+    let offered_amounts = basket
+        .offered
+        .iter()
+        .map(|(_, amount)| amount.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let mut checks = vec![
+        Check::success(),
+        Check::account(&basket.basket).closed().build(),
+    ];
+    for (maker_ata, amount) in maker_atas.iter().zip(&offered_amounts) {
+        checks.push(Check::account(maker_ata).data_slice(64, amount).build());
+    }
+
+    context.process_and_validate_instruction(&refund_basket_instruction(&basket), &checks);
+}
+
+#[test]
+fn test_basket_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut basket = setup_basket(&context, &prelude_accounts, &[1_000], &[2_000], 0);
+    basket.config = setup_paused_config(&context);
+
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[basket.config.admin, basket.config.config],
+        false,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &make_basket_instruction(&basket, 0),
+        &[Check::success()],
+    );
+
+    set_paused(
+        &context,
+        &[basket.config.admin, basket.config.config],
+        true,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &take_basket_instruction(&basket),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+#[test]
+fn test_take_oracle_priced() {
+    let (context, prelude_accounts) = prelude();
+
+    let amount: u64 = 10 * 10u64.pow(DECIMALS as u32);
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    // 2.5 `mint_b` per `mint_a`, +1% spread
+    let now = context.mollusk.sysvars.clock.unix_timestamp;
+    let (price_feed, price_feed_account) =
+        keyed_account_for_price_update(250_000_000, 100_000, -8, now);
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(price_feed, price_feed_account);
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive: 1,
+            seed,
+            oracle: Some((price_feed, 100)),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let funds = 30 * 10u64.pow(DECIMALS as u32);
+    let receive: u64 = 25_250_000;
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, funds);
+    let [_, _, _, _, _, _, taker_ata_a, taker_ata_b, maker_ata_b, ..] = take_pubkeys;
+
+    let amount_bytes = amount.to_le_bytes();
+    let receive_bytes = receive.to_le_bytes();
+    let remaining_bytes = (funds - receive).to_le_bytes();
+
+    // The price feed is required
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+
+    // Checks:
+    //  - Taker receives the vault and pays the oracle price plus the spread.
+    let mut accounts = take_pubkeys.to_vec();
+    accounts.push(price_feed);
+    take(
+        &context,
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&taker_ata_a)
+                .data_slice(64, &amount_bytes)
+                .build(),
+            Check::account(&taker_ata_b)
+                .data_slice(64, &remaining_bytes)
+                .build(),
+            Check::account(&maker_ata_b)
+                .data_slice(64, &receive_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_oracle_priced_floor() {
+    let (context, prelude_accounts) = prelude();
+
+    let amount: u64 = 10 * 10u64.pow(DECIMALS as u32);
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    // 2.5 `mint_b` per `mint_a`, below the maker's floor of 3
+    let now = context.mollusk.sysvars.clock.unix_timestamp;
+    let (price_feed, price_feed_account) =
+        keyed_account_for_price_update(250_000_000, 100_000, -8, now);
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(price_feed, price_feed_account);
+
+    let receive = 30 * 10u64.pow(DECIMALS as u32);
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive,
+            seed,
+            oracle: Some((price_feed, 0)),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let mut accounts = setup_take(&context, make_pubkeys, &config, receive).to_vec();
+    let maker_ata_b = accounts[8];
+    accounts.push(price_feed);
+
+    let receive_bytes = receive.to_le_bytes();
+    take(
+        &context,
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&maker_ata_b)
+                .data_slice(64, &receive_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_oracle_priced_invalid_price_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let now = context.mollusk.sysvars.clock.unix_timestamp;
+    let cases = [
+        // (price, conf, publish_time, error)
+        (
+            250_000_000,
+            100_000,
+            now - 61,
+            ProgramError::Custom(STALE_PRICE),
+        ),
+        (
+            250_000_000,
+            5_000_000,
+            now,
+            ProgramError::Custom(INVALID_PRICE),
+        ),
+        (-1, 0, now, ProgramError::Custom(INVALID_PRICE)),
+    ];
+
+    for (price, conf, publish_time, error) in cases {
+        let amount: u64 = 10 * 10u64.pow(DECIMALS as u32);
+        let seed = random_u64();
+        let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+        let config = setup_config(&context, 0, 0);
+        let make_pubkeys = &accounts.map(|a| a.0);
+
+        let (price_feed, price_feed_account) =
+            keyed_account_for_price_update(price, conf, -8, publish_time);
+        let (other_feed, other_feed_account) =
+            keyed_account_for_price_update(250_000_000, 100_000, -8, now);
+        {
+            let mut store = context.account_store.borrow_mut();
+            store.store_account(price_feed, price_feed_account);
+            store.store_account(other_feed, other_feed_account);
+        }
+
+        make(
+            &context,
+            make_pubkeys,
+            &MakeArgs {
+                amount,
+                receive: 1,
+                seed,
+                oracle: Some((price_feed, 0)),
+                ..Default::default()
+            },
+            &[Check::success()],
+        );
+
+        let mut accounts = setup_take(&context, make_pubkeys, &config, amount).to_vec();
+
+        // Only the maker's price feed is accepted
+        accounts.push(other_feed);
+        take(
+            &context,
+            &accounts,
+            &[Check::err(ProgramError::InvalidAccountData)],
+        );
+
+        accounts.pop();
+        accounts.push(price_feed);
+        take(&context, &accounts, &[Check::err(error)]);
+    }
+}
+
+#[test]
+fn test_make_spread_without_price_feed_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+
+    make(
+        &context,
+        &accounts.map(|a| a.0),
+        &MakeArgs {
+            amount: 1_000,
+            receive: 1_000,
+            seed,
+            oracle: Some((Pubkey::default(), 100)),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_take_many() {
+    let (context, prelude_accounts) = prelude();
+
+    // The second offer asks 3 `mint_b` per `mint_a`, above the taker's limit of 2
+    let fixture = setup_take_many(
+        &context,
+        &prelude_accounts,
+        &[(100, 200), (200, 600), (300, 600)],
+        1_000,
+        100,
+    );
+    let [(maker_1, escrow_1), (maker_2, escrow_2), (maker_3, escrow_3)] = fixture.offers[..] else {
+        panic!("Could not unpack offers")
+    };
+
+    let filled_bytes = 400u64.to_le_bytes();
+    let paid_bytes = (1_000u64 - 800).to_le_bytes();
+    let maker_1_bytes = (200u64 - 2).to_le_bytes();
+    let maker_3_bytes = (600u64 - 6).to_le_bytes();
+    let fee_bytes = 8u64.to_le_bytes();
+
+    // Checks:
+    //  - The first and third offers are filled whole, the second one is skipped.
+    //  - Makers are paid net of the fee, collected once for the whole sweep.
+    context.process_and_validate_instruction(
+        &take_many_instruction(&fixture, 400, 2 * PRICE_SCALE),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&fixture.taker, &fixture.a_mint))
+                .data_slice(64, &filled_bytes)
+                .build(),
+            Check::account(&associated_token_address(&fixture.taker, &fixture.b_mint))
+                .data_slice(64, &paid_bytes)
+                .build(),
+            Check::account(&associated_token_address(&maker_1, &fixture.b_mint))
+                .data_slice(64, &maker_1_bytes)
+                .build(),
+            Check::account(&associated_token_address(&maker_3, &fixture.b_mint))
+                .data_slice(64, &maker_3_bytes)
+                .build(),
+            Check::account(&associated_token_address(
+                &fixture.config.fee_recipient,
+                &fixture.b_mint,
+            ))
+            .data_slice(64, &fee_bytes)
+            .build(),
+            Check::account(&escrow_1).closed().build(),
+            Check::account(&escrow_3).closed().build(),
+            Check::account(&escrow_2).owner(&PROGRAM_ID).build(),
+            Check::account(&associated_token_address(&maker_2, &fixture.b_mint))
+                .data_slice(64, &0u64.to_le_bytes())
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_many_total_not_reached_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_take_many(
+        &context,
+        &prelude_accounts,
+        &[(100, 200), (200, 600), (300, 600)],
+        1_000,
+        0,
+    );
+    let [(_, escrow_1), (_, escrow_2), (_, escrow_3)] = fixture.offers[..] else {
+        panic!("Could not unpack offers")
+    };
+
+    // Only 400 are available within the limit price
+    context.process_and_validate_instruction(
+        &take_many_instruction(&fixture, 401, 2 * PRICE_SCALE),
+        &[Check::err(ProgramError::Custom(TOTAL_NOT_REACHED))],
+    );
+
+    // Filling stops as soon as the total is reached
+    context.process_and_validate_instruction(
+        &take_many_instruction(&fixture, 100, 3 * PRICE_SCALE),
+        &[
+            Check::success(),
+            Check::account(&escrow_1).closed().build(),
+            Check::account(&escrow_2).owner(&PROGRAM_ID).build(),
+            Check::account(&escrow_3).owner(&PROGRAM_ID).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_many_partial_fill() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_take_many(
+        &context,
+        &prelude_accounts,
+        &[(100, 200), (300, 600)],
+        1_000,
+        100,
+    );
+    let [(_, escrow_1), (maker_2, escrow_2)] = fixture.offers[..] else {
+        panic!("Could not unpack offers")
+    };
+
+    let filled_bytes = 250u64.to_le_bytes();
+    let paid_bytes = (1_000u64 - 500).to_le_bytes();
+    let maker_2_bytes = (300u64 - 3).to_le_bytes();
+    let left_bytes = 150u64.to_le_bytes();
+    let receive_left_bytes = 300u64.to_le_bytes();
+
+    // Checks:
+    //  - The offers add up to more than `total`, the taker receives exactly `total`.
+    //  - The second offer is filled for the remaining 150 at its own price, and stays open
+    //    asking for the rest.
+    context.process_and_validate_instruction(
+        &take_many_instruction(&fixture, 250, 2 * PRICE_SCALE),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&fixture.taker, &fixture.a_mint))
+                .data_slice(64, &filled_bytes)
+                .build(),
+            Check::account(&associated_token_address(&fixture.taker, &fixture.b_mint))
+                .data_slice(64, &paid_bytes)
+                .build(),
+            Check::account(&associated_token_address(&maker_2, &fixture.b_mint))
+                .data_slice(64, &maker_2_bytes)
+                .build(),
+            Check::account(&escrow_1).closed().build(),
+            Check::account(&escrow_2)
+                .owner(&PROGRAM_ID)
+                .data_slice(ESCROW_RECEIVE_OFFSET, &receive_left_bytes)
+                .build(),
+            Check::account(&associated_token_address(&escrow_2, &fixture.a_mint))
+                .data_slice(64, &left_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_take_many_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut fixture = setup_take_many(&context, &prelude_accounts, &[(100, 200)], 200, 0);
+    fixture.config = setup_paused_config(&context);
+
+    context.process_and_validate_instruction(
+        &take_many_instruction(&fixture, 100, 2 * PRICE_SCALE),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+#[test]
+fn test_accept_counter() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 100, 0); // 1%
+    let make_pubkeys = accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, b_mint, maker_ata_a, escrow_ata, ..] = make_pubkeys;
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    // Half the vault for less than half the asking price
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    let counter_ata_b = associated_token_address(&fixture.counter, &b_mint);
+
+    let deposit_bytes = 2_000u64.to_le_bytes();
+    let zero_bytes = 0u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 500, 2_000),
+        &[
+            Check::success(),
+            Check::account(&fixture.counter)
+                .owner(&PROGRAM_ID)
+                .space(COUNTER_OFFER_LEN)
+                .build(),
+            Check::account(&fixture.counter)
+                .data_slice(8, escrow.as_ref())
+                .build(),
+            Check::account(&counter_ata_b)
+                .data_slice(64, &deposit_bytes)
+                .build(),
+            Check::account(&associated_token_address(&fixture.proposer, &b_mint))
+                .data_slice(64, &zero_bytes)
+                .build(),
+        ],
+    );
+
+    // Only the maker may accept
+    let mut instruction = accept_counter_instruction(&fixture);
+    instruction.accounts[0] = AccountMeta::new(fixture.proposer, true);
+    context
+        .process_and_validate_instruction(&instruction, &[Check::err(ProgramError::InvalidSeeds)]);
+
+    let proposer_bytes = 500u64.to_le_bytes();
+    let remainder_bytes = 500u64.to_le_bytes();
+    let maker_b_bytes = (2_000u64 - 20).to_le_bytes();
+    let fee_bytes = 20u64.to_le_bytes();
+
+    // Checks:
+    //  - The proposer gets the `token_a` they asked for, the rest goes back to the maker.
+    //  - The maker gets the deposit net of the fee.
+    //  - Escrow, vault and counter offer accounts are closed.
+    context.process_and_validate_instruction(
+        &accept_counter_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&fixture.proposer, &a_mint))
+                .data_slice(64, &proposer_bytes)
+                .build(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &remainder_bytes)
+                .build(),
+            Check::account(&associated_token_address(&maker, &b_mint))
+                .data_slice(64, &maker_b_bytes)
+                .build(),
+            Check::account(&associated_token_address(
+                &fixture.config.fee_recipient,
+                &b_mint,
+            ))
+            .data_slice(64, &fee_bytes)
+            .build(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&escrow_ata).closed().build(),
+            Check::account(&fixture.counter).closed().build(),
+            Check::account(&counter_ata_b).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_accept_counter_with_donation() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 100, 0); // 1%
+    let make_pubkeys = accounts.map(|a| a.0);
+    let [maker, _, _, b_mint, ..] = make_pubkeys;
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    let counter_ata_b = associated_token_address(&fixture.counter, &b_mint);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 500, 2_000),
+        &[Check::success()],
+    );
+
+    donate(&context, &counter_ata_b, 1);
+
+    let maker_b_bytes = (2_000u64 + 1 - 20).to_le_bytes();
+
+    // Checks:
+    //  - The donated token doesn't keep the counter offer's token account from closing, it
+    //    goes to the maker along with the deposit.
+    context.process_and_validate_instruction(
+        &accept_counter_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&maker, &b_mint))
+                .data_slice(64, &maker_b_bytes)
+                .build(),
+            Check::account(&counter_ata_b).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_accept_counter_above_vault_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 5_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 1_001, 5_000),
+        &[Check::success()],
+    );
+
+    context.process_and_validate_instruction(
+        &accept_counter_instruction(&fixture),
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_withdraw_counter() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+    let b_mint = make_pubkeys[3];
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 1_000, 2_000),
+        &[Check::success()],
+    );
+
+    // The proposer can withdraw even once the escrow is gone
+    refund(
+        &context,
+        &refund_accounts(&make_pubkeys),
+        &[Check::success()],
+    );
+
+    let counter_ata_b = associated_token_address(&fixture.counter, &b_mint);
+    let deposit_bytes = 2_000u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &withdraw_counter_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&fixture.proposer, &b_mint))
+                .data_slice(64, &deposit_bytes)
+                .build(),
+            Check::account(&fixture.counter).closed().build(),
+            Check::account(&counter_ata_b).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_counter_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let make_pubkeys = accounts.map(|a| a.0);
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(
+        &context,
+        &make_pubkeys,
+        setup_paused_config(&context),
+        2_000,
+    );
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 500, 2_000),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        false,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 500, 2_000),
+        &[Check::success()],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        true,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &accept_counter_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+#[test]
+fn test_withdraw_counter_while_paused() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+    let b_mint = make_pubkeys[3];
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 1_000, 2_000),
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+
+    let deposit_bytes = 2_000u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &withdraw_counter_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&fixture.proposer, &b_mint))
+                .data_slice(64, &deposit_bytes)
+                .build(),
+            Check::account(&fixture.counter).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_account_discriminators() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 1_000, 2_000),
+        &[Check::success()],
+    );
+
+    let set_discriminator = |discriminator: u8| {
+        let mut store = context.account_store.borrow_mut();
+        let mut account = store.get_account(&fixture.counter).unwrap();
+        account.data[0] = discriminator;
+        store.store_account(fixture.counter, account);
+    };
+
+    // A `Referral` has the same size as a `CounterOffer`, but can't be passed off as one
+    set_discriminator(REFERRAL_DISCRIMINATOR);
+    context.process_and_validate_instruction(
+        &withdraw_counter_instruction(&fixture),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_closed_escrow_cannot_be_revived() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+    let [maker, escrow, ..] = make_pubkeys;
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, &make_pubkeys, &config, 5_000);
+
+    let refund_instruction = refund_instruction(&refund_accounts(&make_pubkeys));
+
+    // Sending the rent back right after the refund, in the same transaction, only makes the
+    // escrow address a system account again: it can't be taken.
+    let revive_instruction = system_instruction::transfer(
+        &maker,
+        &escrow,
+        context
+            .mollusk
+            .sysvars
+            .rent
+            .minimum_balance(ESCROW_BODY_LEN),
+    );
+    let take_instruction = take_instruction(&take_pubkeys, None);
+
+    context.process_and_validate_instruction_chain(&[
+        (&refund_instruction, &[Check::success()]),
+        (
+            &revive_instruction,
+            &[
+                Check::success(),
+                Check::account(&escrow)
+                    .owner(&system_program::ID)
+                    .space(0)
+                    .build(),
+            ],
+        ),
+        (
+            &take_instruction,
+            &[Check::err(ProgramError::InvalidAccountOwner)],
+        ),
+    ]);
+}
+
+#[test]
+fn test_propose_counter_private_escrow_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            taker: Some(Pubkey::new_unique()),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 1_000, 2_000),
+        &[Check::err(ProgramError::Custom(INVALID_TAKER))],
+    );
+}
+
+#[test]
+fn test_take_ignores_min_fill() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+    let [_, escrow, _, _, _, escrow_ata, ..] = make_pubkeys;
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            min_fill: Some(1_000),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let take_pubkeys = setup_take(&context, &make_pubkeys, &config, 5_000);
+    let taker_ata_a = take_pubkeys[6];
+    let filled_bytes = 1_000u64.to_le_bytes();
+
+    // Checks:
+    //  - `Take` fills the whole vault, so even the largest `min_fill` never blocks it.
+    take(
+        &context,
+        &take_pubkeys,
+        &[
+            Check::success(),
+            Check::account(&taker_ata_a)
+                .data_slice(64, &filled_bytes)
+                .build(),
+            Check::account(&escrow_ata).closed().build(),
+            Check::account(&escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_propose_counter_below_min_fill_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = accounts.map(|a| a.0);
+
+    // A minimum fill above the deposit is rejected
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            min_fill: Some(1_001),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    make(
+        &context,
+        &make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 5_000,
+            seed,
+            min_fill: Some(500),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let fixture = setup_counter(&context, &make_pubkeys, config, 2_000);
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 499, 2_000),
+        &[Check::err(ProgramError::Custom(BELOW_MIN_FILL))],
+    );
+
+    context.process_and_validate_instruction(
+        &propose_counter_instruction(&fixture, 500, 2_000),
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_make_and_release_milestones() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let payee_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    let total_bytes = 600u64.to_le_bytes();
+    let first_amount_bytes = 100u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200, 300]),
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow)
+                .owner(&PROGRAM_ID)
+                .space(MILESTONE_HEADER_LEN + 3 * 8)
+                .build(),
+            Check::account(&fixture.escrow)
+                .data_slice(MILESTONE_HEADER_LEN, &first_amount_bytes)
+                .build(),
+            Check::account(&vault).data_slice(64, &total_bytes).build(),
+        ],
+    );
+
+    // Only the release authority may release
+    let mut instruction = release_milestone_instruction(&fixture);
+    instruction.accounts[0] = AccountMeta::new(fixture.funder, true);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    let released_bytes = 100u64.to_le_bytes();
+    let remaining_bytes = 500u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&payee_ata)
+                .data_slice(64, &released_bytes)
+                .build(),
+            Check::account(&vault)
+                .data_slice(64, &remaining_bytes)
+                .build(),
+            Check::account(&fixture.escrow)
+                .data_slice(MILESTONE_RELEASED_COUNT_OFFSET, &[1])
+                .build(),
+        ],
+    );
+
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::success()],
+    );
+
+    // The last tranche closes the escrow
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&payee_ata)
+                .data_slice(64, &total_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_release_last_milestone_with_donation() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let payee_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200]),
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::success()],
+    );
+
+    donate(&context, &vault, 1);
+
+    // The last tranche releases the donated token along with it, and the escrow still closes
+    let released_bytes = (300u64 + 1).to_le_bytes();
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&payee_ata)
+                .data_slice(64, &released_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_reclaim_milestones() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let payer_ata = associated_token_address(&fixture.funder, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200, 300]),
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::success()],
+    );
+
+    context.process_and_validate_instruction(
+        &reclaim_milestones_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(OFFER_NOT_EXPIRED))],
+    );
+
+    // The payer gets back every unreleased tranche
+    context.mollusk.sysvars.clock.unix_timestamp = deadline + 1;
+    let reclaimed_bytes = (1_000u64 - 100).to_le_bytes();
+    context.process_and_validate_instruction(
+        &reclaim_milestones_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&payer_ata)
+                .data_slice(64, &reclaimed_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_milestone_invalid_milestones_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+
+    for amounts in [vec![], vec![10; 9]] {
+        context.process_and_validate_instruction(
+            &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &amounts),
+            &[Check::err(ProgramError::Custom(INVALID_MILESTONES))],
+        );
+    }
+}
+
+#[test]
+fn test_resolve_dispute() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let payer_ata = associated_token_address(&fixture.funder, &fixture.mint);
+    let payee_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &fixture.arbiter, &[100, 200, 300]),
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::success()],
+    );
+
+    context.process_and_validate_instruction(
+        &resolve_instruction(&fixture, 2_500),
+        &[Check::err(ProgramError::Custom(NOT_DISPUTED))],
+    );
+
+    // Only the payer or the payee may open a dispute
+    context.process_and_validate_instruction(
+        &dispute_instruction(&fixture, &fixture.authority),
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    context.process_and_validate_instruction(
+        &dispute_instruction(&fixture, &fixture.recipient),
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow)
+                .data_slice(MILESTONE_DISPUTED_OFFSET, &[1])
+                .build(),
+        ],
+    );
+
+    // Release and reclaim are blocked while the dispute is open
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(ESCROW_DISPUTED))],
+    );
+    context.mollusk.sysvars.clock.unix_timestamp = deadline + 1;
+    context.process_and_validate_instruction(
+        &reclaim_milestones_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(ESCROW_DISPUTED))],
+    );
+
+    // Only the arbiter may resolve
+    let mut instruction = resolve_instruction(&fixture, 2_500);
+    instruction.accounts[0] = AccountMeta::new(fixture.authority, true);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    // 25% of the 500 left in the vault goes to the payee, the rest back to the payer
+    let payee_bytes = (100u64 + 125).to_le_bytes();
+    let payer_bytes = (1_000u64 - 600 + 375).to_le_bytes();
+    context.process_and_validate_instruction(
+        &resolve_instruction(&fixture, 2_500),
+        &[
+            Check::success(),
+            Check::account(&payee_ata)
+                .data_slice(64, &payee_bytes)
+                .build(),
+            Check::account(&payer_ata)
+                .data_slice(64, &payer_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_dispute_without_arbiter_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100]),
+        &[Check::success()],
+    );
+
+    context.process_and_validate_instruction(
+        &dispute_instruction(&fixture, &fixture.funder),
+        &[Check::err(ProgramError::Custom(NOT_DISPUTED))],
+    );
+}
+
+#[test]
+fn test_milestone_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    fixture.config = setup_paused_config(&context);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200]),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        false,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200]),
+        &[Check::success()],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        true,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &release_milestone_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+#[test]
+fn test_reclaim_milestones_while_paused() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let payer_ata = associated_token_address(&fixture.funder, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &Pubkey::default(), &[100, 200]),
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+    context.mollusk.sysvars.clock.unix_timestamp = deadline + 1;
+
+    let reclaimed_bytes = 1_000u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &reclaim_milestones_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&payer_ata)
+                .data_slice(64, &reclaimed_bytes)
+                .build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_resolve_dispute_while_paused() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"milestone", 1_000);
+    let payer_ata = associated_token_address(&fixture.funder, &fixture.mint);
+    let payee_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let deadline = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    context.process_and_validate_instruction(
+        &make_milestone_instruction(&fixture, deadline, &fixture.arbiter, &[100, 300]),
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+
+    context.process_and_validate_instruction(
+        &dispute_instruction(&fixture, &fixture.funder),
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow)
+                .data_slice(MILESTONE_DISPUTED_OFFSET, &[1])
+                .build(),
+        ],
+    );
+
+    // Half of the 400 in the vault to each side
+    let payee_bytes = 200u64.to_le_bytes();
+    let payer_bytes = (1_000u64 - 400 + 200).to_le_bytes();
+    context.process_and_validate_instruction(
+        &resolve_instruction(&fixture, 5_000),
+        &[
+            Check::success(),
+            Check::account(&payee_ata)
+                .data_slice(64, &payee_bytes)
+                .build(),
+            Check::account(&payer_ata)
+                .data_slice(64, &payer_bytes)
+                .build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_claim_vesting() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"vesting", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let beneficiary_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    let total_bytes = 1_000u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &make_vesting_instruction(&fixture, start, start + 100, start + 1_000, 1_000),
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow)
+                .owner(&PROGRAM_ID)
+                .space(VESTING_ESCROW_LEN)
+                .build(),
+            Check::account(&vault).data_slice(64, &total_bytes).build(),
+        ],
+    );
+
+    // Nothing vests before the cliff
+    context.mollusk.sysvars.clock.unix_timestamp = start + 50;
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(NOTHING_TO_CLAIM))],
+    );
+
+    // Only the beneficiary may claim
+    context.mollusk.sysvars.clock.unix_timestamp = start + 500;
+    let mut instruction = claim_instruction(&fixture);
+    instruction.accounts[0] = AccountMeta::new(fixture.funder, true);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    // Past the cliff, vesting is linear from the start
+    let half_bytes = 500u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&beneficiary_ata)
+                .data_slice(64, &half_bytes)
+                .build(),
+            Check::account(&vault).data_slice(64, &half_bytes).build(),
+            Check::account(&fixture.escrow)
+                .data_slice(VESTING_CLAIMED_OFFSET, &half_bytes)
+                .build(),
+        ],
+    );
+
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(NOTHING_TO_CLAIM))],
+    );
+
+    // The last claim closes the escrow
+    context.mollusk.sysvars.clock.unix_timestamp = start + 2_000;
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&beneficiary_ata)
+                .data_slice(64, &total_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_claim_vesting_with_donation() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"vesting", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let beneficiary_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_vesting_instruction(&fixture, start, start, start + 1_000, 1_000),
+        &[Check::success()],
+    );
+
+    donate(&context, &vault, 1);
+
+    // The last claim sweeps the donated token along with the vested total, and the escrow
+    // still closes
+    context.mollusk.sysvars.clock.unix_timestamp = start + 2_000;
+    let claimed_bytes = (1_000u64 + 1).to_le_bytes();
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&beneficiary_ata)
+                .data_slice(64, &claimed_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_vesting_invalid_schedule_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"vesting", 1_000);
+    let now = context.mollusk.sysvars.clock.unix_timestamp;
+
+    for (start, cliff, end) in [
+        (now, now, now),
+        (now + 100, now, now + 1_000),
+        (now, now + 2_000, now + 1_000),
+    ] {
+        context.process_and_validate_instruction(
+            &make_vesting_instruction(&fixture, start, cliff, end, 1_000),
+            &[Check::err(ProgramError::Custom(INVALID_SCHEDULE))],
+        );
+    }
+}
+
+#[test]
+fn test_make_vesting_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut fixture = setup_lock(&context, &prelude_accounts, b"vesting", 1_000);
+    fixture.config = setup_paused_config(&context);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_vesting_instruction(&fixture, start, start, start + 1_000, 1_000),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        false,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &make_vesting_instruction(&fixture, start, start, start + 1_000, 1_000),
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_claim_vesting_while_paused() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"vesting", 1_000);
+    let beneficiary_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_vesting_instruction(&fixture, start, start, start + 1_000, 1_000),
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+    context.mollusk.sysvars.clock.unix_timestamp = start + 500;
+
+    let half_bytes = 500u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &claim_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&beneficiary_ata)
+                .data_slice(64, &half_bytes)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_withdraw_stream() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"stream", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let recipient_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    let deposit_bytes = 1_000u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[
+            Check::success(),
+            Check::account(&fixture.escrow)
+                .owner(&PROGRAM_ID)
+                .space(STREAM_ESCROW_LEN)
+                .build(),
+            Check::account(&vault)
+                .data_slice(64, &deposit_bytes)
+                .build(),
+        ],
+    );
+
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[Check::err(ProgramError::Custom(NOTHING_TO_CLAIM))],
+    );
+
+    // Only the recipient may withdraw
+    context.mollusk.sysvars.clock.unix_timestamp = start + 30;
+    let mut instruction = withdraw_stream_instruction(&fixture);
+    instruction.accounts[0] = AccountMeta::new(fixture.funder, true);
+    context.process_and_validate_instruction(
+        &instruction,
+        &[Check::err(ProgramError::Custom(UNAUTHORIZED))],
+    );
+
+    let accrued_bytes = 300u64.to_le_bytes();
+    let left_bytes = 700u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &accrued_bytes)
+                .build(),
+            Check::account(&vault).data_slice(64, &left_bytes).build(),
+            Check::account(&fixture.escrow)
+                .data_slice(STREAM_WITHDRAWN_OFFSET, &accrued_bytes)
+                .build(),
+        ],
+    );
+
+    // Accrual is capped at the deposit, withdrawing all of it closes the stream
+    context.mollusk.sysvars.clock.unix_timestamp = start + 1_000;
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &deposit_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_withdraw_stream_with_donation() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"stream", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let recipient_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[Check::success()],
+    );
+
+    donate(&context, &vault, 1);
+
+    // The last withdrawal sweeps the donated token along with the deposit, and the stream
+    // still closes
+    context.mollusk.sysvars.clock.unix_timestamp = start + 1_000;
+    let withdrawn_bytes = (1_000u64 + 1).to_le_bytes();
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &withdrawn_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_cancel_stream() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"stream", 1_000);
+    let vault = associated_token_address(&fixture.escrow, &fixture.mint);
+    let sender_ata = associated_token_address(&fixture.funder, &fixture.mint);
+    let recipient_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[Check::success()],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = start + 10;
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[Check::success()],
+    );
+
+    // The recipient gets what accrued since, the sender everything that did not
+    context.mollusk.sysvars.clock.unix_timestamp = start + 40;
+    let accrued_bytes = 400u64.to_le_bytes();
+    let unaccrued_bytes = 600u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &cancel_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &accrued_bytes)
+                .build(),
+            Check::account(&sender_ata)
+                .data_slice(64, &unaccrued_bytes)
+                .build(),
+            Check::account(&vault).closed().build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_stream_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut fixture = setup_lock(&context, &prelude_accounts, b"stream", 1_000);
+    fixture.config = setup_paused_config(&context);
+
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[fixture.config.admin, fixture.config.config],
+        false,
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_withdraw_and_cancel_stream_while_paused() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let fixture = setup_lock(&context, &prelude_accounts, b"stream", 1_000);
+    let sender_ata = associated_token_address(&fixture.funder, &fixture.mint);
+    let recipient_ata = associated_token_address(&fixture.recipient, &fixture.mint);
+
+    let start = context.mollusk.sysvars.clock.unix_timestamp;
+    context.process_and_validate_instruction(
+        &make_stream_instruction(&fixture, 10, 1_000),
+        &[Check::success()],
+    );
+
+    setup_paused_config(&context);
+
+    context.mollusk.sysvars.clock.unix_timestamp = start + 10;
+    let withdrawn_bytes = 100u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &withdraw_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &withdrawn_bytes)
+                .build(),
+        ],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = start + 40;
+    let accrued_bytes = 400u64.to_le_bytes();
+    let unaccrued_bytes = 600u64.to_le_bytes();
+    context.process_and_validate_instruction(
+        &cancel_stream_instruction(&fixture),
+        &[
+            Check::success(),
+            Check::account(&recipient_ata)
+                .data_slice(64, &accrued_bytes)
+                .build(),
+            Check::account(&sender_ata)
+                .data_slice(64, &unaccrued_bytes)
+                .build(),
+            Check::account(&fixture.escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_redeem_hashlock() {
+    let (context, prelude_accounts) = prelude();
+
+    let amount = 1_000;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, make_config, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, 1);
+    let taker = take_pubkeys[0];
+    let taker_ata_a = take_pubkeys[6];
+
+    let preimage = b"cross-chain swap secret";
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive: 1,
+            seed,
+            expiry: Some(context.mollusk.sysvars.clock.unix_timestamp + 100),
+            taker: Some(taker),
+            hashlock: Some(hash(preimage).to_bytes()),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    // The escrow can't be swapped or refunded before its timeout
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::err(ProgramError::Custom(HASHLOCKED))],
+    );
+    refund(
+        &context,
+        &[
+            maker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            maker_ata_a,
+            system_program,
+            token_program,
+            associated_token_program,
+        ],
+        &[Check::err(ProgramError::Custom(HASHLOCKED))],
+    );
+
+    // Anyone may reveal the preimage, the vault always goes to the taker
+    let (relayer, relayer_account) =
+        keyed_account_for_system_account_with_lamports(LAMPORTS_PER_SOL, &system_program);
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(relayer, relayer_account);
+
+    let redeem_pubkeys = [
+        relayer,
+        maker,
+        taker,
+        escrow,
+        a_mint,
+        escrow_ata,
+        taker_ata_a,
+        make_config,
+        system_program,
+        token_program,
+        associated_token_program,
+    ];
+
+    redeem(
+        &context,
+        &redeem_pubkeys,
+        b"wrong secret",
+        &[Check::err(ProgramError::Custom(INVALID_PREIMAGE))],
+    );
+
+    let amount_bytes = amount.to_le_bytes();
+    redeem(
+        &context,
+        &redeem_pubkeys,
+        preimage,
+        &[
+            Check::success(),
+            Check::account(&taker_ata_a)
+                .data_slice(64, &amount_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&escrow_ata).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_redeem_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let amount = 1_000;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, _, escrow_ata, _, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, 1);
+    let taker = take_pubkeys[0];
+    let taker_ata_a = take_pubkeys[6];
+
+    let preimage = b"cross-chain swap secret";
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive: 1,
+            seed,
+            expiry: Some(context.mollusk.sysvars.clock.unix_timestamp + 100),
+            taker: Some(taker),
+            hashlock: Some(hash(preimage).to_bytes()),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    let config = setup_paused_config(&context);
+    let redeem_pubkeys = [
+        taker,
+        maker,
+        taker,
+        escrow,
+        a_mint,
+        escrow_ata,
+        taker_ata_a,
+        config.config,
+        system_program,
+        token_program,
+        associated_token_program,
+    ];
+
+    redeem(
+        &context,
+        &redeem_pubkeys,
+        preimage,
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+
+    set_paused(
+        &context,
+        &[config.admin, config.config],
+        false,
+        &[Check::success()],
+    );
+    redeem(
+        &context,
+        &redeem_pubkeys,
+        preimage,
+        &[Check::success(), Check::account(&escrow).closed().build()],
+    );
+}
+
+#[test]
+fn test_refund_hashlock_after_timeout() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let amount = 1_000;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, config, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let taker = Pubkey::new_unique();
+    let preimage = b"cross-chain swap secret";
+    let expiry = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount,
+            receive: 1,
+            seed,
+            expiry: Some(expiry),
+            taker: Some(taker),
+            hashlock: Some(hash(preimage).to_bytes()),
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    context.mollusk.sysvars.clock.unix_timestamp = expiry + 1;
+
+    redeem(
+        &context,
+        &[
+            maker,
+            maker,
+            taker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            associated_token_address(&taker, &a_mint),
+            config,
+            system_program,
+            token_program,
+            associated_token_program,
+        ],
+        preimage,
+        &[Check::err(ProgramError::Custom(OFFER_EXPIRED))],
+    );
+
+    let amount_bytes = amount.to_le_bytes();
+    refund(
+        &context,
+        &[
+            maker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            maker_ata_a,
+            system_program,
+            token_program,
+            associated_token_program,
+        ],
+        &[
+            Check::success(),
+            Check::account(&maker_ata_a)
+                .data_slice(64, &amount_bytes)
+                .build(),
+            Check::account(&escrow).closed().build(),
+        ],
+    );
+}
+
+#[test]
+fn test_make_hashlock_without_taker_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+
+    make(
+        &context,
+        make_pubkeys,
+        &MakeArgs {
+            amount: 1_000,
+            receive: 1,
+            seed,
+            expiry: Some(context.mollusk.sysvars.clock.unix_timestamp + 100),
+            hashlock: Some(hash(b"secret").to_bytes()),
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_market_stats() {
+    let (context, prelude_accounts) = prelude();
+
+    let amount = 1_000;
+    let receive: u64 = 250;
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, amount, seed);
+    let config = setup_config(&context, 0, 0);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, _, a_mint, b_mint, _, _, _, system_program, ..] = *make_pubkeys;
+
+    let market = market_pda(&a_mint, &b_mint);
+    init_market(
+        &context,
+        &[maker, market, a_mint, b_mint, system_program],
+        &[
+            Check::success(),
+            Check::account(&market)
+                .owner(&PROGRAM_ID)
+                .space(MARKET_LEN)
+                .build(),
+        ],
+    );
+
+    make(
+        &context,
+        &[&make_pubkeys[..], &[market]].concat(),
+        &MakeArgs {
+            amount,
+            receive,
+            seed,
+            ..Default::default()
+        },
+        &[
+            Check::success(),
+            Check::account(&market)
+                .data_slice(MARKET_OPEN_COUNT_OFFSET, &1u64.to_le_bytes())
+                .build(),
+        ],
+    );
+
+    // An indexed escrow can't be settled without its market
+    let take_pubkeys = setup_take(&context, make_pubkeys, &config, receive);
+    take(
+        &context,
+        &take_pubkeys,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+
+    let mut stats = 0u64.to_le_bytes().to_vec(); // open_count
+    stats.extend_from_slice(&amount.to_le_bytes()); // volume_a
+    stats.extend_from_slice(&receive.to_le_bytes()); // volume_b
+    take(
+        &context,
+        &[&take_pubkeys[..], &[market]].concat(),
+        &[
+            Check::success(),
+            Check::account(&market)
+                .data_slice(MARKET_OPEN_COUNT_OFFSET, &stats)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_market_refund() {
+    let (context, prelude_accounts) = prelude();
+
+    let seed = random_u64();
+    let accounts = setup_make(&context, &prelude_accounts, 1_000, seed);
+    let make_pubkeys = &accounts.map(|a| a.0);
+    let [maker, escrow, a_mint, b_mint, maker_ata_a, escrow_ata, _, system_program, token_program, associated_token_program] =
+        *make_pubkeys;
+
+    let market = market_pda(&a_mint, &b_mint);
+    init_market(
+        &context,
+        &[maker, market, a_mint, b_mint, system_program],
+        &[Check::success()],
+    );
+
+    // The market must be the one of the escrow's pair
+    let other_market = market_pda(&b_mint, &a_mint);
+    init_market(
+        &context,
+        &[maker, other_market, b_mint, a_mint, system_program],
+        &[Check::success()],
+    );
+    make(
+        &context,
+        &[&make_pubkeys[..], &[other_market]].concat(),
+        &MakeArgs {
+            amount: 1_000,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+
+    make(
+        &context,
+        &[&make_pubkeys[..], &[market]].concat(),
+        &MakeArgs {
+            amount: 1_000,
+            receive: 1,
+            seed,
+            ..Default::default()
+        },
+        &[Check::success()],
+    );
+
+    refund(
+        &context,
+        &[
+            maker,
+            escrow,
+            a_mint,
+            escrow_ata,
+            maker_ata_a,
+            system_program,
+            token_program,
+            associated_token_program,
+            market,
+        ],
+        &[
+            Check::success(),
+            Check::account(&escrow).closed().build(),
+            Check::account(&market)
+                .data_slice(MARKET_OPEN_COUNT_OFFSET, &0u64.to_le_bytes())
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_fill_order() {
+    let (context, prelude_accounts) = prelude();
+
+    let order = setup_order(&context, &prelude_accounts, 1_000, 4_000, 0, 250);
+    let maker = order.maker.pubkey();
+
+    let fee = 4_000 * 250 / 10_000;
+    let zero_bytes = 0u64.to_le_bytes();
+    let amount_bytes = order.amount.to_le_bytes();
+    let maker_receive_bytes = (order.receive - fee).to_le_bytes();
+    let fee_bytes = fee.to_le_bytes();
+    let nonce_bytes = order.nonce.to_le_bytes();
+    let nonce = nonce_pda(&PROGRAM_ID, &maker, order.nonce);
+
+    // Checks:
+    //  - The maker's delegated `mint_a` moves straight to the taker.
+    //  - The taker pays `receive` of `mint_b`, split between maker and fee recipient.
+    //  - The order's nonce is recorded.
+    fill_order(
+        &context,
+        &order,
+        &order.maker,
+        &[
+            Check::success(),
+            Check::account(&associated_token_address(&maker, &order.a_mint))
+                .data_slice(64, &zero_bytes)
+                .build(),
+            Check::account(&associated_token_address(&order.taker, &order.a_mint))
+                .data_slice(64, &amount_bytes)
+                .build(),
+            Check::account(&associated_token_address(&order.taker, &order.b_mint))
+                .data_slice(64, &zero_bytes)
+                .build(),
+            Check::account(&associated_token_address(&maker, &order.b_mint))
+                .data_slice(64, &maker_receive_bytes)
+                .build(),
+            Check::account(&associated_token_address(
+                &order.config.fee_recipient,
+                &order.b_mint,
+            ))
+            .data_slice(64, &fee_bytes)
+            .build(),
+            Check::account(&nonce)
+                .owner(&PROGRAM_ID)
+                .space(ORDER_NONCE_LEN)
+                .build(),
+            Check::account(&nonce).data_slice(8, maker.as_ref()).build(),
+            Check::account(&nonce).data_slice(40, &nonce_bytes).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_fill_order_twice_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let order = setup_order(&context, &prelude_accounts, 1_000, 4_000, 0, 0);
+
+    fill_order(&context, &order, &order.maker, &[Check::success()]);
+    fill_order(
+        &context,
+        &order,
+        &order.maker,
+        &[Check::err(ProgramError::Custom(ORDER_ALREADY_FILLED))],
+    );
+}
+
+#[test]
+fn test_fill_order_invalid_signature_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let order = setup_order(&context, &prelude_accounts, 1_000, 4_000, 0, 0);
+
+    // A valid ed25519 signature, but not by the maker
+    fill_order(
+        &context,
+        &order,
+        &Keypair::new(),
+        &[Check::err(ProgramError::Custom(INVALID_SIGNATURE))],
+    );
+}
+
+#[test]
+fn test_fill_order_expired_fails() {
+    let (mut context, prelude_accounts) = prelude();
+
+    let expiry = context.mollusk.sysvars.clock.unix_timestamp + 100;
+    let order = setup_order(&context, &prelude_accounts, 1_000, 4_000, expiry, 0);
+    context.mollusk.sysvars.clock.unix_timestamp = expiry + 1;
+
+    fill_order(
+        &context,
+        &order,
+        &order.maker,
+        &[Check::err(ProgramError::Custom(OFFER_EXPIRED))],
+    );
+}
+
+#[test]
+fn test_fill_order_while_paused_fails() {
+    let (context, prelude_accounts) = prelude();
+
+    let mut order = setup_order(&context, &prelude_accounts, 1_000, 4_000, 0, 0);
+    order.config = setup_paused_config(&context);
+
+    fill_order(
+        &context,
+        &order,
+        &order.maker,
+        &[Check::err(ProgramError::Custom(PROGRAM_PAUSED))],
+    );
+}
+
+fn keyed_account_for_system_account_with_lamports(
+    lamports: u64,
+    owner: &Pubkey,
+) -> (Pubkey, Account) {
+    let payer = Pubkey::new_unique();
+    let payer_account = Account::new(lamports, 0, owner);
+
+    (payer, payer_account)
+}
+
+fn escrow_pda(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), seed.to_le_bytes().as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+fn market_pda(a_mint: &Pubkey, b_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"market", a_mint.as_ref(), b_mint.as_ref()], &PROGRAM_ID).0
+}
+
+fn counter_pda(escrow: &Pubkey, proposer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"counter", escrow.as_ref(), proposer.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address_with_program_id(
+        owner,
+        mint,
+        &spl_token::ID,
+    )
+}
+
+#[derive(Default)]
+struct MakeArgs {
+    amount: u64,
+    receive: u64,
+    seed: u64,
+    expiry: Option<i64>,
+    taker: Option<Pubkey>,
+    mode: Option<u8>,
+    collection: Option<Pubkey>,
+    oracle: Option<(Pubkey, u16)>, // (price_feed, spread_bps)
+    hashlock: Option<[u8; 32]>,
+    min_fill: Option<u64>,
+}
+
+impl MakeArgs {
+    fn instruction_data(&self) -> Vec<u8> {
+        let mut instruction_data = vec![];
+        instruction_data.extend_from_slice(&[0]); // make instruction DISCRIMINATOR
+        instruction_data.extend_from_slice(self.amount.to_le_bytes().as_ref());
+        instruction_data.extend_from_slice(self.receive.to_le_bytes().as_ref());
+        instruction_data.extend_from_slice(self.seed.to_le_bytes().as_ref());
+        // Each optional field requires all the previous ones
+        let with_hashlock = self.hashlock.is_some() || self.min_fill.is_some();
+        let with_oracle = with_hashlock || self.oracle.is_some();
+        let with_collection = with_oracle || self.collection.is_some();
+        let with_mode = with_collection || self.mode.is_some();
+        let with_taker = with_mode || self.taker.is_some();
+        let with_expiry = with_taker || self.expiry.is_some();
+
+        if with_expiry {
+            instruction_data.extend_from_slice(self.expiry.unwrap_or(0).to_le_bytes().as_ref());
+        }
+        if with_taker {
+            instruction_data.extend_from_slice(self.taker.unwrap_or_default().as_ref());
+        }
+        if with_mode {
+            instruction_data.push(self.mode.unwrap_or(0));
+        }
+        if with_collection {
+            instruction_data.extend_from_slice(self.collection.unwrap_or_default().as_ref());
+        }
+        if with_oracle {
+            let (price_feed, spread_bps) = self.oracle.unwrap_or_default();
+            instruction_data.extend_from_slice(price_feed.as_ref());
+            instruction_data.extend_from_slice(spread_bps.to_le_bytes().as_ref());
+        }
+        if with_hashlock {
+            instruction_data.extend_from_slice(self.hashlock.unwrap_or_default().as_ref());
+        }
+        if let Some(min_fill) = self.min_fill {
+            instruction_data.extend_from_slice(min_fill.to_le_bytes().as_ref());
+        }
+
+        instruction_data
+    }
+}
+
+fn make(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    args: &MakeArgs,
+    checks: &[Check],
+) -> ContextResult {
+    let [maker, escrow, a_mint, b_mint, maker_ata, escrow_ata, config, system_program, token_program, associated_token_program, metadata_a @ ..] =
+        accounts
+    else {
+        panic!("Could not unpack accounts in make")
+    };
+
+    let mut account_metas = vec![
+        AccountMeta::new(*maker, true),
+        AccountMeta::new(*escrow, false),
+        AccountMeta::new(*a_mint, false),
+        AccountMeta::new(*b_mint, false),
+        AccountMeta::new(*maker_ata, false),
+        AccountMeta::new(*escrow_ata, false),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*system_program, false),
+        AccountMeta::new(*token_program, false),
+        AccountMeta::new(*associated_token_program, false),
+    ];
+    // Optional `[metadata_a]` and `[market]`
+    account_metas.extend(metadata_a.iter().map(|key| AccountMeta::new(*key, false)));
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        args.instruction_data().as_slice(),
+        account_metas,
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn take(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    checks: &[Check],
+) -> ContextResult {
+    take_with_limits(context, accounts, None, checks)
+}
+
+/// `take` with the taker's `(max_pay, min_receive)` slippage limits.
+fn take_with_limits(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    limits: Option<(u64, u64)>,
+    checks: &[Check],
+) -> ContextResult {
+    context.process_and_validate_instruction(&take_instruction(accounts, limits), checks)
+}
+
+fn take_instruction(accounts: &[Pubkey], limits: Option<(u64, u64)>) -> Instruction {
+    let [taker, maker, escrow, a_mint, b_mint, escrow_ata, taker_ata_a, taker_ata_b, maker_ata_b, config, fee_recipient, fee_recipient_ata_b, system_program, token_program, associated_token_program, referral @ ..] =
+        accounts
+    else {
+        panic!("Could not unpack accounts in take")
+    };
+
+    let mut account_metas = vec![
+        AccountMeta::new(*taker, true),
+        AccountMeta::new(*maker, false),
+        AccountMeta::new(*escrow, false),
+        AccountMeta::new_readonly(*a_mint, false),
+        AccountMeta::new_readonly(*b_mint, false),
+        AccountMeta::new(*escrow_ata, false),
+        AccountMeta::new(*taker_ata_a, false),
+        AccountMeta::new(*taker_ata_b, false),
+        AccountMeta::new(*maker_ata_b, false),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new_readonly(*fee_recipient, false),
+        AccountMeta::new(*fee_recipient_ata_b, false),
+        AccountMeta::new_readonly(*system_program, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*associated_token_program, false),
+    ];
+    // Optional `[referrer_ata_b, referral]`, `[price_feed]` and `[market]`
+    account_metas.extend(referral.iter().map(|key| AccountMeta::new(*key, false)));
+
+    let mut instruction_data = vec![1]; // take instruction DISCRIMINATOR
+    if let Some((max_pay, min_receive)) = limits {
+        instruction_data.extend_from_slice(max_pay.to_le_bytes().as_ref());
+        instruction_data.extend_from_slice(min_receive.to_le_bytes().as_ref());
+    }
+
+    Instruction::new_with_bytes(PROGRAM_ID, &instruction_data, account_metas)
+}
+
+fn init_config(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    fee_bps: u16,
+    referral_share_bps: u16,
+    fee_recipient: &Pubkey,
+    checks: &[Check],
+) -> ContextResult {
+    let [authority, config, program_data] = accounts else {
+        panic!("Could not unpack accounts in init_config")
+    };
+    let (system_program, _) = keyed_account_for_system_program();
+
+    let mut instruction_data = vec![3]; // init_config instruction DISCRIMINATOR
+    instruction_data.extend_from_slice(&fee_bps.to_le_bytes());
+    instruction_data.extend_from_slice(&referral_share_bps.to_le_bytes());
+    instruction_data.extend_from_slice(fee_recipient.as_ref());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &instruction_data,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*config, false),
+            AccountMeta::new_readonly(*program_data, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn update_config(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    fee_bps: u16,
+    referral_share_bps: u16,
+    fee_recipient: &Pubkey,
+    admin: &Pubkey,
+    checks: &[Check],
+) -> ContextResult {
+    let [signer, config] = accounts else {
+        panic!("Could not unpack accounts in update_config")
+    };
+
+    let mut instruction_data = vec![4]; // update_config instruction DISCRIMINATOR
+    instruction_data.extend_from_slice(&fee_bps.to_le_bytes());
+    instruction_data.extend_from_slice(&referral_share_bps.to_le_bytes());
+    instruction_data.extend_from_slice(fee_recipient.as_ref());
+    instruction_data.extend_from_slice(admin.as_ref());
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &instruction_data,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*config, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+/// `args` is `[receive, deposit, withdraw]`.
+fn update(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    args: [u64; 3],
+    checks: &[Check],
+) -> ContextResult {
+    let [maker, escrow, a_mint, maker_ata_a, escrow_ata, config, token_program] = accounts else {
+        panic!("Could not unpack accounts in update")
+    };
+
+    let mut instruction_data = vec![7]; // update instruction DISCRIMINATOR
+    for arg in args {
+        instruction_data.extend_from_slice(&arg.to_le_bytes());
+    }
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &instruction_data,
+        vec![
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*a_mint, false),
+            AccountMeta::new(*maker_ata_a, false),
+            AccountMeta::new(*escrow_ata, false),
+            AccountMeta::new_readonly(*config, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn make_basket_instruction(basket: &BasketFixture, expiry: i64) -> Instruction {
+    let mut instruction_data = vec![8]; // make_basket instruction DISCRIMINATOR
+    instruction_data.extend_from_slice(&basket.seed.to_le_bytes());
+    instruction_data.extend_from_slice(&expiry.to_le_bytes());
+    instruction_data.push(basket.offered.len() as u8);
+    instruction_data.push(basket.requested.len() as u8);
+
+    let mut accounts = vec![
+        AccountMeta::new(basket.maker, true),
+        AccountMeta::new(basket.basket, false),
+        AccountMeta::new_readonly(basket.config.config, false),
+        AccountMeta::new_readonly(basket.system_program, false),
+        AccountMeta::new_readonly(basket.token_program, false),
+        AccountMeta::new_readonly(basket.associated_token_program, false),
+    ];
+
+    for (mint, amount) in &basket.offered {
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+        accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(associated_token_address(&basket.maker, mint), false),
+            AccountMeta::new(associated_token_address(&basket.basket, mint), false),
+        ]);
+    }
+    for (mint, amount) in &basket.requested {
+        instruction_data.extend_from_slice(&amount.to_le_bytes());
+        accounts.push(AccountMeta::new_readonly(*mint, false));
+    }
+
+    Instruction::new_with_bytes(PROGRAM_ID, &instruction_data, accounts)
+}
+
+fn take_basket_instruction(basket: &BasketFixture) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(basket.taker, true),
+        AccountMeta::new(basket.maker, false),
+        AccountMeta::new(basket.basket, false),
+        AccountMeta::new_readonly(basket.config.config, false),
+        AccountMeta::new_readonly(basket.config.fee_recipient, false),
+        AccountMeta::new_readonly(basket.system_program, false),
+        AccountMeta::new_readonly(basket.token_program, false),
+        AccountMeta::new_readonly(basket.associated_token_program, false),
+    ];
+
+    for (mint, _) in &basket.offered {
+        accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(associated_token_address(&basket.basket, mint), false),
+            AccountMeta::new(associated_token_address(&basket.taker, mint), false),
+        ]);
+    }
+    for (mint, _) in &basket.requested {
+        accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(associated_token_address(&basket.taker, mint), false),
+            AccountMeta::new(associated_token_address(&basket.maker, mint), false),
+            AccountMeta::new(
+                associated_token_address(&basket.config.fee_recipient, mint),
+                false,
+            ),
+        ]);
+    }
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[9], // take_basket instruction DISCRIMINATOR
+        accounts,
+    )
+}
+
+fn take_many_instruction(fixture: &TakeManyFixture, total: u64, limit_price: u64) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(fixture.taker, true),
+        AccountMeta::new_readonly(fixture.a_mint, false),
+        AccountMeta::new_readonly(fixture.b_mint, false),
+        AccountMeta::new(
+            associated_token_address(&fixture.taker, &fixture.a_mint),
+            false,
+        ),
+        AccountMeta::new(
+            associated_token_address(&fixture.taker, &fixture.b_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(fixture.config.config, false),
+        AccountMeta::new_readonly(fixture.config.fee_recipient, false),
+        AccountMeta::new(
+            associated_token_address(&fixture.config.fee_recipient, &fixture.b_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(fixture.system_program, false),
+        AccountMeta::new_readonly(fixture.token_program, false),
+        AccountMeta::new_readonly(fixture.associated_token_program, false),
+    ];
+
+    for (maker, escrow) in &fixture.offers {
+        accounts.extend([
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(associated_token_address(escrow, &fixture.a_mint), false),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(associated_token_address(maker, &fixture.b_mint), false),
+        ]);
+    }
+
+    let mut data = vec![12]; // take_many instruction DISCRIMINATOR
+    data.extend_from_slice(&total.to_le_bytes());
+    data.extend_from_slice(&limit_price.to_le_bytes());
+
+    Instruction::new_with_bytes(PROGRAM_ID, &data, accounts)
+}
+
+fn propose_counter_instruction(fixture: &CounterFixture, amount: u64, deposit: u64) -> Instruction {
+    let [_, escrow, _, b_mint, _, _, _, system_program, token_program, associated_token_program] =
+        fixture.make;
+
+    let mut data = vec![13]; // propose_counter instruction DISCRIMINATOR
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&deposit.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(fixture.proposer, true),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new(fixture.counter, false),
+            AccountMeta::new_readonly(b_mint, false),
+            AccountMeta::new(associated_token_address(&fixture.proposer, &b_mint), false),
+            AccountMeta::new(associated_token_address(&fixture.counter, &b_mint), false),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(associated_token_program, false),
+        ],
+    )
+}
+
+fn accept_counter_instruction(fixture: &CounterFixture) -> Instruction {
+    let [maker, escrow, a_mint, b_mint, maker_ata_a, escrow_ata, _, system_program, token_program, associated_token_program] =
+        fixture.make;
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[14], // accept_counter instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(maker, true),
+            AccountMeta::new(fixture.proposer, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(fixture.counter, false),
+            AccountMeta::new_readonly(a_mint, false),
+            AccountMeta::new_readonly(b_mint, false),
+            AccountMeta::new(escrow_ata, false),
+            AccountMeta::new(associated_token_address(&fixture.counter, &b_mint), false),
+            AccountMeta::new(associated_token_address(&fixture.proposer, &a_mint), false),
+            AccountMeta::new(maker_ata_a, false),
+            AccountMeta::new(associated_token_address(&maker, &b_mint), false),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(fixture.config.fee_recipient, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.config.fee_recipient, &b_mint),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(associated_token_program, false),
+        ],
+    )
+}
+
+fn withdraw_counter_instruction(fixture: &CounterFixture) -> Instruction {
+    let [_, escrow, _, b_mint, _, _, _, system_program, token_program, associated_token_program] =
+        fixture.make;
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[15], // withdraw_counter instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.proposer, true),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new(fixture.counter, false),
+            AccountMeta::new_readonly(b_mint, false),
+            AccountMeta::new(associated_token_address(&fixture.counter, &b_mint), false),
+            AccountMeta::new(associated_token_address(&fixture.proposer, &b_mint), false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(associated_token_program, false),
+        ],
+    )
+}
+
+fn make_milestone_instruction(
+    fixture: &LockFixture,
+    deadline: i64,
+    arbiter: &Pubkey,
+    amounts: &[u64],
+) -> Instruction {
+    let mut data = vec![16]; // make_milestone instruction DISCRIMINATOR
+    data.extend_from_slice(&fixture.seed.to_le_bytes());
+    data.extend_from_slice(&deadline.to_le_bytes());
+    data.extend_from_slice(fixture.recipient.as_ref());
+    data.extend_from_slice(fixture.authority.as_ref());
+    data.extend_from_slice(arbiter.as_ref());
+    data.push(amounts.len() as u8);
+    for amount in amounts {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(fixture.funder, true),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn release_milestone_instruction(fixture: &LockFixture) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[17], // release_milestone instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.authority, true),
+            AccountMeta::new(fixture.funder, false),
+            AccountMeta::new_readonly(fixture.recipient, false),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.recipient, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn reclaim_milestones_instruction(fixture: &LockFixture) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[18], // reclaim_milestones instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.funder, true),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn dispute_instruction(fixture: &LockFixture, party: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[19], // dispute instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new_readonly(*party, true),
+            AccountMeta::new(fixture.escrow, false),
+        ],
+    )
+}
+
+fn resolve_instruction(fixture: &LockFixture, payee_bps: u16) -> Instruction {
+    let mut data = vec![20]; // resolve instruction DISCRIMINATOR
+    data.extend_from_slice(&payee_bps.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(fixture.arbiter, true),
+            AccountMeta::new(fixture.funder, false),
+            AccountMeta::new_readonly(fixture.recipient, false),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.recipient, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn make_vesting_instruction(
+    fixture: &LockFixture,
+    start: i64,
+    cliff: i64,
+    end: i64,
+    total: u64,
+) -> Instruction {
+    let mut data = vec![21]; // make_vesting instruction DISCRIMINATOR
+    data.extend_from_slice(&fixture.seed.to_le_bytes());
+    data.extend_from_slice(fixture.recipient.as_ref());
+    data.extend_from_slice(&start.to_le_bytes());
+    data.extend_from_slice(&cliff.to_le_bytes());
+    data.extend_from_slice(&end.to_le_bytes());
+    data.extend_from_slice(&total.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(fixture.funder, true),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn claim_instruction(fixture: &LockFixture) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[22], // claim instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.recipient, true),
+            AccountMeta::new(fixture.funder, false),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.recipient, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn make_stream_instruction(fixture: &LockFixture, rate: u64, deposit: u64) -> Instruction {
+    let mut data = vec![23]; // make_stream instruction DISCRIMINATOR
+    data.extend_from_slice(&fixture.seed.to_le_bytes());
+    data.extend_from_slice(fixture.recipient.as_ref());
+    data.extend_from_slice(&rate.to_le_bytes());
+    data.extend_from_slice(&deposit.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(fixture.funder, true),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.config.config, false),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn withdraw_stream_instruction(fixture: &LockFixture) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[24], // withdraw_stream instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.recipient, true),
+            AccountMeta::new(fixture.funder, false),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.recipient, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn cancel_stream_instruction(fixture: &LockFixture) -> Instruction {
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[25], // cancel_stream instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(fixture.funder, true),
+            AccountMeta::new_readonly(fixture.recipient, false),
+            AccountMeta::new(fixture.escrow, false),
+            AccountMeta::new_readonly(fixture.mint, false),
+            AccountMeta::new(
+                associated_token_address(&fixture.escrow, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.funder, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(&fixture.recipient, &fixture.mint),
+                false,
+            ),
+            AccountMeta::new_readonly(fixture.system_program, false),
+            AccountMeta::new_readonly(fixture.token_program, false),
+            AccountMeta::new_readonly(fixture.associated_token_program, false),
+        ],
+    )
+}
+
+fn refund_basket_instruction(basket: &BasketFixture) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(basket.maker, true),
+        AccountMeta::new(basket.basket, false),
+        AccountMeta::new_readonly(basket.system_program, false),
+        AccountMeta::new_readonly(basket.token_program, false),
+        AccountMeta::new_readonly(basket.associated_token_program, false),
+    ];
+
+    for (mint, _) in &basket.offered {
+        accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(associated_token_address(&basket.basket, mint), false),
+            AccountMeta::new(associated_token_address(&basket.maker, mint), false),
+        ]);
+    }
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[10], // refund_basket instruction DISCRIMINATOR
+        accounts,
+    )
+}
+
+/// Maps `make` accounts to `refund` order.
+fn refund_accounts(make_accounts: &[Pubkey; 10]) -> [Pubkey; 8] {
+    let [maker, escrow, a_mint, _, maker_ata_a, escrow_ata, _, system_program, token_program, associated_token_program] =
+        *make_accounts;
+
+    [
+        maker,
+        escrow,
+        a_mint,
+        escrow_ata,
+        maker_ata_a,
+        system_program,
+        token_program,
+        associated_token_program,
+    ]
+}
+
+fn refund(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    checks: &[Check],
+) -> ContextResult {
+    context.process_and_validate_instruction(&refund_instruction(accounts), checks)
+}
+
+fn refund_instruction(accounts: &[Pubkey]) -> Instruction {
+    let [maker, escrow, a_mint, escrow_ata, maker_ata_a, system_program, token_program, associated_token_program, market @ ..] =
+        accounts
+    else {
+        panic!("Could not unpack accounts in refund")
+    };
+
+    let mut account_metas = vec![
+        AccountMeta::new(*maker, true),
+        AccountMeta::new(*escrow, false),
+        AccountMeta::new_readonly(*a_mint, false),
+        AccountMeta::new(*escrow_ata, false),
+        AccountMeta::new(*maker_ata_a, false),
+        AccountMeta::new_readonly(*system_program, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*associated_token_program, false),
+    ];
+    // Optional `[market]`
+    account_metas.extend(market.iter().map(|key| AccountMeta::new(*key, false)));
+
+    Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[5], // refund instruction DISCRIMINATOR
+        account_metas,
+    )
+}
+
+fn set_paused(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    paused: bool,
+    checks: &[Check],
+) -> ContextResult {
+    let [admin, config] = accounts else {
+        panic!("Could not unpack accounts in set_paused")
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[6, paused as u8], // set_paused instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*config, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn register_referral(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    checks: &[Check],
+) -> ContextResult {
+    let [admin, config, referrer, b_mint, referral, system_program] = accounts else {
+        panic!("Could not unpack accounts in register_referral")
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[29], // register_referral instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*config, false),
+            AccountMeta::new_readonly(*referrer, false),
+            AccountMeta::new_readonly(*b_mint, false),
+            AccountMeta::new(*referral, false),
+            AccountMeta::new_readonly(*system_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn close_expired(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    checks: &[Check],
+) -> ContextResult {
+    let [payer, maker, escrow, a_mint, escrow_ata, maker_ata_a, system_program, token_program, associated_token_program] =
+        accounts
+    else {
+        panic!("Could not unpack accounts in close_expired")
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[2], // close_expired instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*a_mint, false),
+            AccountMeta::new(*escrow_ata, false),
+            AccountMeta::new(*maker_ata_a, false),
+            AccountMeta::new_readonly(*system_program, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(*associated_token_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn init_market(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    checks: &[Check],
+) -> ContextResult {
+    let [payer, market, a_mint, b_mint, system_program] = accounts else {
+        panic!("Could not unpack accounts in init_market")
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &[27], // init_market instruction DISCRIMINATOR
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*market, false),
+            AccountMeta::new_readonly(*a_mint, false),
+            AccountMeta::new_readonly(*b_mint, false),
+            AccountMeta::new_readonly(*system_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+fn redeem(
+    context: &MolluskContext<InMemoryAccountStore>,
+    accounts: &[Pubkey],
+    preimage: &[u8],
+    checks: &[Check],
+) -> ContextResult {
+    let [payer, maker, taker, escrow, a_mint, escrow_ata, taker_ata_a, config, system_program, token_program, associated_token_program] =
+        accounts
+    else {
+        panic!("Could not unpack accounts in redeem")
+    };
+
+    let mut data = vec![26]; // redeem instruction DISCRIMINATOR
+    data.extend_from_slice(preimage);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new_readonly(*taker, false),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(*a_mint, false),
+            AccountMeta::new(*escrow_ata, false),
+            AccountMeta::new(*taker_ata_a, false),
+            AccountMeta::new_readonly(*config, false),
+            AccountMeta::new_readonly(*system_program, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(*associated_token_program, false),
+        ],
+    );
+
+    context.process_and_validate_instruction(&instruction, checks)
+}
+
+/// Signs `order` with `signer` and settles it, preceded by the ed25519 verification the
+/// runtime would run first in the same transaction.
+fn fill_order(
+    context: &MolluskContext<InMemoryAccountStore>,
+    order: &OrderFixture,
+    signer: &Keypair,
+    checks: &[Check],
+) -> ContextResult {
+    let maker = order.maker.pubkey();
+
+    let message = order_message(
+        &PROGRAM_ID,
+        &maker,
+        &order.a_mint,
+        &order.b_mint,
+        order.amount,
+        order.receive,
+        order.seed,
+        order.expiry,
+        order.nonce,
+    );
+    let signature = signer.sign_message(&message);
+    let verify_instruction = new_ed25519_instruction_with_signature(
+        &message,
+        signature.as_array(),
+        &signer.pubkey().to_bytes(),
+    );
+
+    let account_metas = vec![
+        AccountMeta::new(order.taker, true),
+        AccountMeta::new_readonly(maker, false),
+        AccountMeta::new_readonly(order.a_mint, false),
+        AccountMeta::new_readonly(order.b_mint, false),
+        AccountMeta::new(associated_token_address(&maker, &order.a_mint), false),
+        AccountMeta::new(associated_token_address(&order.taker, &order.a_mint), false),
+        AccountMeta::new(associated_token_address(&order.taker, &order.b_mint), false),
+        AccountMeta::new(associated_token_address(&maker, &order.b_mint), false),
+        AccountMeta::new_readonly(delegate_pda(&PROGRAM_ID), false),
+        AccountMeta::new(nonce_pda(&PROGRAM_ID, &maker, order.nonce), false),
+        AccountMeta::new_readonly(order.config.config, false),
+        AccountMeta::new_readonly(order.config.fee_recipient, false),
+        AccountMeta::new(
+            associated_token_address(&order.config.fee_recipient, &order.b_mint),
+            false,
+        ),
+        AccountMeta::new_readonly(INSTRUCTIONS_SYSVAR_ID, false),
+        AccountMeta::new_readonly(order.system_program, false),
+        AccountMeta::new_readonly(order.token_program, false),
+        AccountMeta::new_readonly(order.associated_token_program, false),
+    ];
+
+    let mut data = vec![11]; // fill_order instruction DISCRIMINATOR
+    data.extend_from_slice(&order.amount.to_le_bytes());
+    data.extend_from_slice(&order.receive.to_le_bytes());
+    data.extend_from_slice(&order.seed.to_le_bytes());
+    data.extend_from_slice(&order.expiry.to_le_bytes());
+    data.extend_from_slice(&order.nonce.to_le_bytes());
+
+    let instruction = Instruction::new_with_bytes(PROGRAM_ID, &data, account_metas);
 
-    let result = context.process_and_validate_instruction(&instruction, checks);
+    let (instructions_sysvar, instructions_sysvar_account) =
+        keyed_account_for_instructions_sysvar(&[&verify_instruction, &instruction], 1);
+    context
+        .account_store
+        .borrow_mut()
+        .store_account(instructions_sysvar, instructions_sysvar_account);
 
-    result
+    context.process_and_validate_instruction_chain(&[
+        (&verify_instruction, &[Check::success()]),
+        (&instruction, checks),
+    ])
 }
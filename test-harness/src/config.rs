@@ -0,0 +1,83 @@
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::{pubkey, rent::Rent};
+
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+// See `escrow::state::AccountHeader`
+pub const ACCOUNT_HEADER_LEN: usize = 8;
+
+pub const CONFIG_DISCRIMINATOR: u8 = 2;
+pub const REFERRAL_DISCRIMINATOR: u8 = 3;
+
+pub const CONFIG_LEN: usize = ACCOUNT_HEADER_LEN + 32 + 32 + 2 + 2 + 1 + 1;
+pub const REFERRAL_LEN: usize = ACCOUNT_HEADER_LEN + 32 + 32 + 8 + 8 + 1;
+
+/// The account header of a current version account of the given type.
+pub fn account_header(discriminator: u8) -> [u8; ACCOUNT_HEADER_LEN] {
+    [discriminator, 1, 0, 0, 0, 0, 0, 0]
+}
+
+pub fn config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], program_id)
+}
+
+pub fn referral_pda(program_id: &Pubkey, referrer: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"referral", referrer.as_ref(), mint.as_ref()], program_id)
+}
+
+pub fn keyed_account_for_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fee_recipient: &Pubkey,
+    fee_bps: u16,
+    referral_share_bps: u16,
+) -> (Pubkey, Account) {
+    let (config, bump) = config_pda(program_id);
+
+    let mut data = Vec::with_capacity(CONFIG_LEN);
+    data.extend_from_slice(&account_header(CONFIG_DISCRIMINATOR));
+    data.extend_from_slice(admin.as_ref());
+    data.extend_from_slice(fee_recipient.as_ref());
+    data.extend_from_slice(&fee_bps.to_le_bytes());
+    data.extend_from_slice(&referral_share_bps.to_le_bytes());
+    data.push(0); // paused
+    data.push(bump);
+
+    let account = Account {
+        lamports: Rent::default().minimum_balance(CONFIG_LEN),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    (config, account)
+}
+
+/// The upgradeable loader `ProgramData` account of `program_id`, holding `upgrade_authority`.
+pub fn keyed_account_for_program_data(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+) -> (Pubkey, Account) {
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+
+    // UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }
+    let mut data = Vec::with_capacity(45);
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: BPF_LOADER_UPGRADEABLE_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+
+    (program_data, account)
+}
@@ -0,0 +1,47 @@
+//! Fixtures shared by the program tests, and a `Mollusk` running every program of the workspace
+//! for scenarios spanning several of them.
+//!
+//! Programs are loaded from the workspace's `target/deploy`, where `cargo build-sbf` puts them, so
+//! the programs have to be built before the tests.
+
+use mollusk_svm::{program::keyed_account_for_system_program, Mollusk, MolluskContext};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+pub mod config;
+pub mod store;
+pub mod token;
+
+pub use store::InMemoryAccountStore;
+
+pub const ESCROW_PROGRAM_ID: Pubkey = Pubkey::new_from_array(escrow::ID);
+pub const VAULT_PROGRAM_ID: Pubkey = Pubkey::new_from_array(vault::ID);
+
+/// Path of a program built by `cargo build-sbf`, as expected by `Mollusk::new`.
+pub fn program_path(name: &str) -> String {
+    format!("{}/../target/deploy/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// A context running the escrow, vault, SPL Token and Associated Token programs, along with the
+/// accounts of the token, associated token and system programs.
+pub fn context() -> (MolluskContext<InMemoryAccountStore>, Vec<(Pubkey, Account)>) {
+    let mut mollusk = Mollusk::new(&ESCROW_PROGRAM_ID, &program_path("escrow"));
+    mollusk.add_program(
+        &VAULT_PROGRAM_ID,
+        &program_path("vault"),
+        &mollusk_svm::program::loader_keys::LOADER_V3,
+    );
+
+    mollusk_svm_programs_token::token::add_program(&mut mollusk);
+    mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+
+    let context = mollusk.with_context(InMemoryAccountStore::default());
+
+    let accounts = vec![
+        mollusk_svm_programs_token::token::keyed_account(),
+        mollusk_svm_programs_token::associated_token::keyed_account(),
+        keyed_account_for_system_program(),
+    ];
+
+    (context, accounts)
+}
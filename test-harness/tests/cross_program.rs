@@ -0,0 +1,138 @@
+use mollusk_svm::{account_store::AccountStore, result::Check};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use test_harness::{
+    config::keyed_account_for_config,
+    context,
+    token::{keyed_account_for_mint_default, keyed_account_for_native_associated_token_account},
+    ESCROW_PROGRAM_ID, VAULT_PROGRAM_ID,
+};
+
+/// Withdraws SOL from a vault, wraps it and offers it in an escrow, all in one context.
+#[test]
+fn test_escrow_sol_withdrawn_from_vault() {
+    let (context, prelude_accounts) = context();
+    let [token_program, associated_token_program, system_program] = &prelude_accounts[..] else {
+        panic!("Could not fetch prelude accounts");
+    };
+
+    let starting_lamports = 10 * LAMPORTS_PER_SOL;
+    let deposit = 2 * LAMPORTS_PER_SOL;
+    let offered = LAMPORTS_PER_SOL;
+
+    let maker = Pubkey::new_unique();
+    let (vault, _) = Pubkey::find_program_address(&[b"vault", maker.as_ref()], &VAULT_PROGRAM_ID);
+    let (maker_ata, maker_ata_account) = keyed_account_for_native_associated_token_account(&maker);
+    let (native_mint, native_mint_account) =
+        keyed_account_for_mint_default(&Pubkey::default(), Some(spl_token::native_mint::ID), 9);
+    let (b_mint, b_mint_account) = keyed_account_for_mint_default(&maker, None, 6);
+    let (config, config_account) = keyed_account_for_config(
+        &ESCROW_PROGRAM_ID,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        0,
+        0,
+    );
+
+    let seed = 42u64;
+    let (escrow, _) = Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), seed.to_le_bytes().as_ref()],
+        &ESCROW_PROGRAM_ID,
+    );
+    let escrow_ata =
+        get_associated_token_address_with_program_id(&escrow, &native_mint, &spl_token::ID);
+
+    {
+        let mut store = context.account_store.borrow_mut();
+        for (pubkey, account) in prelude_accounts.iter().cloned().chain([
+            (maker, Account::new(starting_lamports, 0, &system_program.0)),
+            (maker_ata, maker_ata_account),
+            (native_mint, native_mint_account),
+            (b_mint, b_mint_account),
+            (config, config_account),
+        ]) {
+            store.store_account(pubkey, account);
+        }
+    }
+
+    // Vault: deposit, then withdraw everything back to the maker
+    let vault_metas = vec![
+        AccountMeta::new(maker, true),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program.0, false),
+    ];
+
+    let mut deposit_data = vec![0];
+    deposit_data.extend_from_slice(&deposit.to_le_bytes());
+    context.process_and_validate_instruction(
+        &Instruction::new_with_bytes(VAULT_PROGRAM_ID, &deposit_data, vault_metas.clone()),
+        &[
+            Check::success(),
+            Check::account(&vault).lamports(deposit).build(),
+        ],
+    );
+
+    context.process_and_validate_instruction(
+        &Instruction::new_with_bytes(VAULT_PROGRAM_ID, &[1], vault_metas),
+        &[
+            Check::success(),
+            Check::account(&maker).lamports(starting_lamports).build(),
+            Check::account(&vault).lamports(0).build(),
+        ],
+    );
+
+    // Wrap part of the withdrawn SOL
+    context.process_and_validate_instruction(
+        &system_instruction::transfer(&maker, &maker_ata, offered),
+        &[Check::success()],
+    );
+    context.process_and_validate_instruction(
+        &spl_token::instruction::sync_native(&spl_token::ID, &maker_ata).unwrap(),
+        &[
+            Check::success(),
+            Check::account(&maker_ata)
+                .data_slice(64, &offered.to_le_bytes())
+                .build(),
+        ],
+    );
+
+    // Escrow: offer the wrapped SOL
+    let mut make_data = vec![0];
+    make_data.extend_from_slice(&offered.to_le_bytes()); // amount
+    make_data.extend_from_slice(&1_000u64.to_le_bytes()); // receive
+    make_data.extend_from_slice(&seed.to_le_bytes());
+
+    context.process_and_validate_instruction(
+        &Instruction::new_with_bytes(
+            ESCROW_PROGRAM_ID,
+            &make_data,
+            vec![
+                AccountMeta::new(maker, true),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(native_mint, false),
+                AccountMeta::new(b_mint, false),
+                AccountMeta::new(maker_ata, false),
+                AccountMeta::new(escrow_ata, false),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(system_program.0, false),
+                AccountMeta::new(token_program.0, false),
+                AccountMeta::new(associated_token_program.0, false),
+            ],
+        ),
+        &[
+            Check::success(),
+            Check::account(&maker_ata)
+                .data_slice(64, &0u64.to_le_bytes())
+                .build(),
+            Check::account(&escrow_ata)
+                .data_slice(64, &offered.to_le_bytes())
+                .build(),
+        ],
+    );
+}
@@ -1,9 +1,7 @@
 use core::mem::size_of;
 
-use pinocchio::{
-    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
-    ProgramResult,
-};
+use accounts_derive::Accounts;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 use pinocchio_system::instructions::Transfer;
 
 /*
@@ -11,40 +9,13 @@ use pinocchio_system::instructions::Transfer;
  * Accounts Context
  * =============================
  */
+#[derive(Accounts)]
 pub struct DepositAccounts<'a> {
+    #[signer]
     pub owner: &'a AccountInfo,
+    #[pda(seeds = [b"vault", owner.key()])]
     pub vault: &'a AccountInfo,
-}
-
-impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
-    type Error = ProgramError;
-
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [owner, vault, _] = accounts else {
-            return Err(ProgramError::NotEnoughAccountKeys);
-        };
-
-        // Account Checks
-        if !owner.is_signer() {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        if !vault.is_owned_by(&pinocchio_system::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        // Must have 0 lamports => ensures fresh deposit.
-        if vault.lamports().ne(&0) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        let (vault_key, _) = find_program_address(&[b"vault", owner.key()], &crate::ID);
-        if vault_key.ne(vault.key()) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        Ok(Self { owner, vault })
-    }
+    pub system_program: &'a AccountInfo,
 }
 
 /*
@@ -92,6 +63,15 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Deposit<'a> {
         let accounts = DepositAccounts::try_from(accounts)?;
         let instruction_data = DepositInstructionData::try_from(data)?;
 
+        if !accounts.vault.is_owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // Must have 0 lamports => ensures fresh deposit.
+        if accounts.vault.lamports().ne(&0) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
         Ok(Self {
             accounts,
             instruction_data,
@@ -1,8 +1,8 @@
+use accounts_derive::Accounts;
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     program_error::ProgramError,
-    pubkey::find_program_address,
     ProgramResult,
 };
 use pinocchio_system::instructions::Transfer;
@@ -12,46 +12,16 @@ use pinocchio_system::instructions::Transfer;
  * Accounts Context
  * =============================
  */
+#[derive(Accounts)]
 pub struct WithdrawAccounts<'a> {
+    #[signer]
     pub owner: &'a AccountInfo,
+    #[pda(seeds = [b"vault", owner.key()], bump = bumps)]
     pub vault: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
     pub bumps: [u8; 1],
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
-    type Error = ProgramError;
-
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [owner, vault, _] = accounts else {
-            return Err(ProgramError::NotEnoughAccountKeys);
-        };
-
-        if !owner.is_signer() {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        if !vault.is_owned_by(&pinocchio_system::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
-        if vault.lamports().eq(&0) {
-            return Err(ProgramError::InvalidAccountData);
-        }
-
-        let (vault_key, bump) = find_program_address(&[b"vault", owner.key().as_ref()], &crate::ID);
-
-        if &vault_key != vault.key() {
-            return Err(ProgramError::InvalidAccountData);
-        }
-
-        Ok(Self {
-            owner,
-            vault,
-            bumps: [bump],
-        })
-    }
-}
-
 /*
  * =============================
  * Instruction Context
@@ -67,6 +37,14 @@ impl<'a> TryFrom<&'a [AccountInfo]> for Withdraw<'a> {
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
         let accounts = WithdrawAccounts::try_from(accounts)?;
 
+        if !accounts.vault.is_owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        if accounts.vault.lamports().eq(&0) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
         Ok(Self { accounts })
     }
 }
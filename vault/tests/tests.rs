@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
 use mollusk_svm::{
     account_store::AccountStore,
     program::keyed_account_for_system_program,
-    result::{compare, Check, ContextResult},
+    result::{Check, ContextResult},
     Mollusk, MolluskContext,
 };
 use solana_account::Account;
@@ -12,6 +10,7 @@ use solana_sdk::{
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
 };
+use test_harness::{program_path, InMemoryAccountStore};
 
 // const ID: Pubkey = solana_sdk::pubkey!("37knY9pFnowzNYwKiPhDPWJwid633Zzd2YsDhoiMKLUg");
 const ID: [u8; 32] = [
@@ -21,25 +20,9 @@ const ID: [u8; 32] = [
 
 const PROGRAM_ID: &Pubkey = &Pubkey::new_from_array(ID);
 
-// Simple in-memory account store implementation
-#[derive(Default)]
-struct InMemoryAccountStore {
-    accounts: HashMap<Pubkey, Account>,
-}
-
-impl AccountStore for InMemoryAccountStore {
-    fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
-        self.accounts.get(pubkey).cloned()
-    }
-
-    fn store_account(&mut self, pubkey: Pubkey, account: Account) {
-        self.accounts.insert(pubkey, account);
-    }
-}
-
 #[test]
 fn test_deposit() {
-    let mollusk = Mollusk::new(PROGRAM_ID, "./target/deploy/vault");
+    let mollusk = Mollusk::new(PROGRAM_ID, &program_path("vault"));
 
     let (system_program, system_program_account) = keyed_account_for_system_program();
 
@@ -58,7 +41,7 @@ fn test_deposit() {
 
     let mut store = InMemoryAccountStore::default();
     for (pubkey, account) in &accounts {
-        store.store_account(pubkey.clone(), account.clone());
+        store.store_account(*pubkey, account.clone());
     }
     let context = mollusk.with_context(store);
 
@@ -87,8 +70,10 @@ fn test_deposit() {
             .build(),
     ];
 
-    let config = &context.mollusk.config;
-    compare::compare!(config, "token amount", 0, 1);
+    // `mollusk_svm::result::compare::compare!` is private to `mollusk-svm-result` and expands to
+    // `return false`, so this check can't be expressed here:
+    // let config = &context.mollusk.config;
+    // compare::compare!(config, "token amount", 0, 1);
 
     // Test withdrraw
     let _withdraw_result = withdraw(&context, account_pubkeys, withdraw_checks);
@@ -121,9 +106,7 @@ fn deposit(
         ],
     );
 
-    let result = context.process_and_validate_instruction(&instruction, checks);
-
-    result
+    context.process_and_validate_instruction(&instruction, checks)
 }
 
 fn withdraw(
@@ -148,7 +131,5 @@ fn withdraw(
         ],
     );
 
-    let result = context.process_and_validate_instruction(&instruction, checks);
-
-    result
+    context.process_and_validate_instruction(&instruction, checks)
 }